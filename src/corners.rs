@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+
+use crate::VertexPositionColorTextureNormalTangentBitangent as Vertex;
+
+/// Gives every triangle corner in `indices` its value from `values`, with `set`. A vertex used by corners with
+/// different values is copied to the end of `vertices` for each extra value, and `indices` is updated to use
+/// the copies.
+///
+/// Returns the vertex each copy was made from, so per vertex data such as blend shapes can be copied too.
+pub(crate) fn assign<const N: usize>(vertices: &mut Vec<Vertex>, indices: &mut [u32], values: Vec<[f32; N]>, set: impl Fn(&mut Vertex, [f32; N])) -> Vec<u32> {
+    let mut assigned: Vec<Option<[f32; N]>> = vec![None; vertices.len()];
+    let mut copies: HashMap<(u32, [u32; N]), u32> = HashMap::new();
+    let mut copied = Vec::new();
+
+    for (index, value) in indices.iter_mut().zip(values) {
+        let vertex = *index as usize;
+        match assigned[vertex] {
+            None => {
+                assigned[vertex] = Some(value);
+                set(&mut vertices[vertex], value);
+            }
+
+            Some(existing) if existing == value => {}
+
+            Some(_) => {
+                *index = *copies.entry((*index, value.map(f32::to_bits))).or_insert_with(|| {
+                    let mut copy = vertices[vertex];
+                    set(&mut copy, value);
+                    vertices.push(copy);
+                    copied.push(vertex as u32);
+
+                    (vertices.len() - 1) as u32
+                });
+            }
+        }
+    }
+
+    copied
+}
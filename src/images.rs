@@ -0,0 +1,94 @@
+use crate::{importers::ImportError, ColorSpace, Pixels};
+
+/// Decodes a PNG or JPEG image to RGBA pixels. Returns `None` if the image is in any other format.
+pub(crate) fn decode(data: &[u8], path: &str, color_space: ColorSpace) -> Result<Option<Pixels>, ImportError> {
+    let invalid = |message: String| ImportError::InvalidData { path: path.to_string(), message };
+
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        decode_png(data, color_space).map(Some).map_err(|e| invalid(format!("Invalid PNG: {e}")))
+    } else if data.starts_with(&[0xFF, 0xD8]) {
+        decode_jpeg(data, color_space).map(Some).map_err(|e| invalid(format!("Invalid JPEG: {e}")))
+    } else {
+        Ok(None)
+    }
+}
+
+fn decode_png(data: &[u8], color_space: ColorSpace) -> Result<Pixels, png::DecodingError> {
+    let mut decoder = png::Decoder::new(data);
+    // Palettes, transparency chunks and bit depths below 8 are all expanded to plain samples.
+    decoder.set_transformations(png::Transformations::EXPAND);
+
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+
+    let sixteen = info.bit_depth == png::BitDepth::Sixteen;
+    let sample_size = if sixteen { 2 } else { 1 };
+    let channels = info.color_type.samples();
+
+    let mut pixels = Vec::with_capacity(info.width as usize * info.height as usize * 4 * sample_size);
+    for pixel in buffer.chunks_exact(channels * sample_size) {
+        // PNG stores 16 bit samples big endian.
+        let sample = |i: usize| if sixteen { u16::from_be_bytes([pixel[i * 2], pixel[i * 2 + 1]]) } else { pixel[i] as u16 };
+        let opaque = if sixteen { u16::MAX } else { u8::MAX as u16 };
+
+        let rgba = match channels {
+            1 => [sample(0), sample(0), sample(0), opaque],
+            2 => [sample(0), sample(0), sample(0), sample(1)],
+            3 => [sample(0), sample(1), sample(2), opaque],
+            _ => [sample(0), sample(1), sample(2), sample(3)]
+        };
+
+        for value in rgba {
+            if sixteen {
+                pixels.extend_from_slice(&value.to_le_bytes());
+            } else {
+                pixels.push(value as u8);
+            }
+        }
+    }
+
+    Ok(Pixels {
+        width: info.width,
+        height: info.height,
+        channels: 4,
+        bit_depth: if sixteen { 16 } else { 8 },
+        color_space,
+        data: pixels
+    })
+}
+
+fn decode_jpeg(data: &[u8], color_space: ColorSpace) -> Result<Pixels, jpeg_decoder::Error> {
+    use jpeg_decoder::PixelFormat;
+
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let samples = decoder.decode()?;
+    let info = decoder.info().ok_or_else(|| jpeg_decoder::Error::Format("No frame header.".to_string()))?;
+
+    let pixels = match info.pixel_format {
+        PixelFormat::L8 => samples.iter().flat_map(|&l| [l, l, l, u8::MAX]).collect(),
+
+        // The decoder stores 16 bit samples in native byte order.
+        PixelFormat::L16 => samples.chunks_exact(2).flat_map(|l| {
+            let l = u16::from_ne_bytes([l[0], l[1]]).to_le_bytes();
+            [l, l, l, u16::MAX.to_le_bytes()].concat()
+        }).collect(),
+
+        PixelFormat::RGB24 => samples.chunks_exact(3).flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX]).collect(),
+
+        PixelFormat::CMYK32 => samples.chunks_exact(4).flat_map(|cmyk| {
+            let channel = |value: u8| ((u8::MAX - value) as u32 * (u8::MAX - cmyk[3]) as u32 / u8::MAX as u32) as u8;
+            [channel(cmyk[0]), channel(cmyk[1]), channel(cmyk[2]), u8::MAX]
+        }).collect()
+    };
+
+    Ok(Pixels {
+        width: info.width as u32,
+        height: info.height as u32,
+        channels: 4,
+        bit_depth: if info.pixel_format == PixelFormat::L16 { 16 } else { 8 },
+        color_space,
+        data: pixels
+    })
+}
@@ -4,11 +4,12 @@ use crate::*;
 
 #[repr(C)]
 pub struct Mesh {
-    pub vertices:     *const VertexPositionColorTextureNormalTangentBitangent,
-    pub num_vertices: usize,
-    pub indices:      *const u32,
-    pub num_indices:  usize,
-    pub material:     usize
+    pub vertices:       *const VertexPositionColorTextureNormalTangentBitangent,
+    pub num_vertices:   usize,
+    pub indices:        *const u32,
+    pub num_indices:    usize,
+    pub primitive_type: PrimitiveType,
+    pub material:       usize
 }
 
 #[repr(C)]
@@ -24,11 +25,32 @@ pub struct Material {
     pub num_textures:     usize
 }
 
+/// Null pointers mean the texture has no path, data, MIME type or pixels.
 #[repr(C)]
 pub struct Texture {
-    pub path:        *const c_char,
-    pub data:        *const u8,
-    pub data_length: usize
+    pub path:          *const c_char,
+    pub data:          *const u8,
+    pub data_length:   usize,
+    pub mime_type:     *const c_char,
+    pub width:         u32,
+    pub height:        u32,
+    pub channels:      u8,
+    pub bit_depth:     u8,
+    pub color_space:   ColorSpace,
+    pub pixels:        *const u8,
+    pub pixels_length: usize,
+    pub sampler:       Sampler
+}
+
+/// The filters are only meaningful if their `has_` flag is set, otherwise the renderer should pick them.
+#[repr(C)]
+pub struct Sampler {
+    pub has_mag_filter: bool,
+    pub mag_filter:     TextureFilter,
+    pub has_min_filter: bool,
+    pub min_filter:     TextureFilter,
+    pub wrap_s:         WrapMode,
+    pub wrap_t:         WrapMode
 }
 
 #[repr(C)]
@@ -41,9 +63,25 @@ pub struct Scene {
     pub num_textures:  usize
 }
 
+/// Loads a scene, with its textures decoded to pixels if impasse was built with image support.
+fn load_scene(path: &str) -> Result<crate::Scene, ImportError> {
+    #[allow(unused_mut)]
+    let mut scene = crate::Scene::from_gltf(path)?;
+
+    #[cfg(feature = "images")]
+    scene.decode_textures(std::path::Path::new(path).parent().unwrap_or(std::path::Path::new("")), &mut importers::FileResolver)?;
+
+    Ok(scene)
+}
+
+/// Returns false, and sets `scene` to null, if the scene could not be loaded.
 #[no_mangle]
-pub unsafe extern "C" fn iaLoadScene(path: *const c_char, scene: *mut *mut Scene) {
-    let rs_scene = crate::Scene::from_gltf(CStr::from_ptr(path).to_str().unwrap()).unwrap();
+pub unsafe extern "C" fn iaLoadScene(path: *const c_char, scene: *mut *mut Scene) -> bool {
+    let Some(rs_scene) = CStr::from_ptr(path).to_str().ok().and_then(|path| load_scene(path).ok()) else {
+        *scene = std::ptr::null_mut();
+        return false;
+    };
+
     let mut meshes = Vec::with_capacity(rs_scene.meshes.len());
     for mesh in rs_scene.meshes {
         meshes.push(Box::into_raw(Box::new(Mesh {
@@ -53,6 +91,7 @@ pub unsafe extern "C" fn iaLoadScene(path: *const c_char, scene: *mut *mut Scene
             num_indices: mesh.indices.len(),
             indices: mesh.indices.as_ptr(),
 
+            primitive_type: mesh.primitive_type,
             material: mesh.material
         })) as *const _);
 
@@ -79,12 +118,38 @@ pub unsafe extern "C" fn iaLoadScene(path: *const c_char, scene: *mut *mut Scene
 
     let mut textures = Vec::with_capacity(rs_scene.textures.len());
     for texture in rs_scene.textures {
-        let path = CString::new(texture.path.unwrap()).unwrap();
+        let c_string = |value: Option<String>| value.and_then(|v| CString::new(v).ok()).map_or(std::ptr::null(), |v| v.into_raw() as *const c_char);
+        let leak = |value: Option<Vec<u8>>| value.map_or((std::ptr::null(), 0), |v| {
+            let v = Box::leak(v.into_boxed_slice());
+            (v.as_ptr(), v.len())
+        });
+
+        let (data, data_length) = leak(texture.data);
+
+        let (width, height, channels, bit_depth, color_space) = texture.pixels.as_ref()
+            .map_or((0, 0, 0, 0, ColorSpace::Linear), |p| (p.width, p.height, p.channels, p.bit_depth, p.color_space));
+        let (pixels, pixels_length) = leak(texture.pixels.map(|p| p.data));
 
         textures.push(Box::into_raw(Box::new(Texture {
-            path: path.into_raw() as *const _,
-            data: std::ptr::null(),
-            data_length: 0
+            path: c_string(texture.path),
+            data,
+            data_length,
+            mime_type: c_string(texture.mime_type),
+            width,
+            height,
+            channels,
+            bit_depth,
+            color_space,
+            pixels,
+            pixels_length,
+            sampler: Sampler {
+                has_mag_filter: texture.sampler.mag_filter.is_some(),
+                mag_filter: texture.sampler.mag_filter.unwrap_or(TextureFilter::Linear),
+                has_min_filter: texture.sampler.min_filter.is_some(),
+                min_filter: texture.sampler.min_filter.unwrap_or(TextureFilter::Linear),
+                wrap_s: texture.sampler.wrap_s,
+                wrap_t: texture.sampler.wrap_t
+            }
         })) as *const _);
     }
 
@@ -103,6 +168,8 @@ pub unsafe extern "C" fn iaLoadScene(path: *const c_char, scene: *mut *mut Scene
 
     let scene_ptr = Box::into_raw(scene_box);
     *scene = scene_ptr;
+
+    true
 }
 
 #[no_mangle]
@@ -0,0 +1,387 @@
+use std::io;
+
+use super::{
+    buffer::DecoderBuffer,
+    corner_table::{Corners, INVALID},
+    invalid,
+    prediction::{MeshData, Octahedron, PredictionScheme, Positions, PREDICTION_NONE},
+    rans
+};
+
+pub const ATTRIBUTE_POSITION: u8 = 0;
+
+const DECODER_GENERIC: u8 = 0;
+const DECODER_INTEGER: u8 = 1;
+const DECODER_QUANTIZATION: u8 = 2;
+const DECODER_NORMALS: u8 = 3;
+
+const DT_UINT32: u8 = 6;
+const DT_FLOAT32: u8 = 9;
+const DT_FLOAT64: u8 = 10;
+
+/// Decoded attribute values, with `num_components` entries per value.
+pub enum AttributeValues {
+    Float(Vec<f32>),
+    Int(Vec<i64>)
+}
+
+/// How the values of an attribute were visited by the encoder, and which corner each one came from.
+pub struct EncodingData {
+    pub vertex_to_value: Vec<i32>,
+    pub value_to_corner: Vec<u32>
+}
+
+pub struct Attribute {
+    pub attribute_type: u8,
+    pub data_type:      u8,
+    pub num_components: usize,
+    pub unique_id:      u32,
+    pub decoder_type:   u8,
+    /// The quantized values prediction schemes work on.
+    pub portable:       Vec<i32>,
+    pub point_to_value: Vec<u32>,
+    pub values:         Option<AttributeValues>,
+    quantization:       Option<(Vec<f32>, f32, u32)>,
+    octahedron:         Option<Octahedron>
+}
+
+fn data_type_size(data_type: u8) -> Option<usize> {
+    match data_type {
+        1 | 2 | 11 => Some(1),
+        3 | 4 => Some(2),
+        5 | DT_UINT32 | DT_FLOAT32 => Some(4),
+        7 | 8 | DT_FLOAT64 => Some(8),
+        _ => None
+    }
+}
+
+impl Attribute {
+    /// Reads an attribute description from an attributes decoder header.
+    pub fn decode_header(buffer: &mut DecoderBuffer) -> Result<Attribute, io::Error> {
+        let attribute_type = buffer.read_u8()?;
+        let data_type = buffer.read_u8()?;
+        let num_components = buffer.read_u8()? as usize;
+        let _normalized = buffer.read_u8()?;
+        let unique_id = buffer.read_varint_u32()?;
+
+        if data_type_size(data_type).is_none() || num_components == 0 {
+            return Err(invalid("Invalid Draco attribute description."));
+        }
+
+        Ok(Attribute {
+            attribute_type,
+            data_type,
+            num_components,
+            unique_id,
+            decoder_type: DECODER_GENERIC,
+            portable: Vec::new(),
+            point_to_value: Vec::new(),
+            values: None,
+            quantization: None,
+            octahedron: None
+        })
+    }
+
+    fn num_portable_components(&self) -> usize {
+        if self.decoder_type == DECODER_NORMALS { 2 } else { self.num_components }
+    }
+
+    /// Decodes the values of the attribute, in the order of `point_ids`.
+    pub fn decode_values<'a, T: Corners>(&mut self, buffer: &mut DecoderBuffer<'a>, point_ids: &[u32], mesh: Option<&MeshData<T>>,
+                                         positions: Option<&Positions>, num_corners: usize) -> Result<(), io::Error> {
+        let num_values = point_ids.len();
+
+        if self.decoder_type == DECODER_GENERIC {
+            let size = data_type_size(self.data_type).unwrap();
+            let bytes = buffer.read_bytes(num_values.checked_mul(self.num_components * size).ok_or_else(|| invalid("Too many Draco values."))?)?;
+            self.values = Some(convert_raw(bytes, self.data_type, size));
+            return Ok(());
+        }
+
+        if !matches!(self.decoder_type, DECODER_INTEGER | DECODER_QUANTIZATION | DECODER_NORMALS) {
+            return Err(invalid(&format!("Unrecognized Draco attribute decoder {}.", self.decoder_type)));
+        }
+
+        if self.decoder_type != DECODER_INTEGER && self.data_type != DT_FLOAT32 {
+            return Err(invalid("Quantized Draco attributes must be floats."));
+        }
+
+        if self.decoder_type == DECODER_NORMALS && self.num_components != 3 {
+            return Err(invalid("Draco normals must have three components."));
+        }
+
+        let method = buffer.read_i8()?;
+        let mut scheme = if method != PREDICTION_NONE {
+            let transform = buffer.read_i8()?;
+            let scheme = PredictionScheme::new(method, transform, self.decoder_type == DECODER_NORMALS, mesh.is_some())?;
+            if scheme.needs_positions() && positions.is_none() {
+                return Err(invalid("Draco prediction requires positions that have not been decoded."));
+            }
+
+            Some(scheme)
+        } else {
+            None
+        };
+
+        let num_components = self.num_portable_components();
+        let num_entries = num_values * num_components;
+        let mut values: Vec<i32> = if buffer.read_u8()? > 0 {
+            rans::decode_symbols(buffer, num_entries, num_components)?.into_iter().map(|v| v as i32).collect()
+        } else {
+            let num_bytes = buffer.read_u8()? as usize;
+            if !(1..=4).contains(&num_bytes) {
+                return Err(invalid("Invalid Draco value size."));
+            }
+
+            let bytes = buffer.read_bytes(num_entries.checked_mul(num_bytes).ok_or_else(|| invalid("Too many Draco values."))?)?;
+            bytes.chunks_exact(num_bytes).map(|c| c.iter().rev().fold(0u32, |acc, &b| (acc << 8) | b as u32) as i32).collect()
+        };
+
+        if !scheme.as_ref().is_some_and(|s| s.are_corrections_positive()) {
+            for value in &mut values {
+                *value = rans::symbol_to_signed(*value as u32);
+            }
+        }
+
+        if let Some(scheme) = &mut scheme {
+            scheme.decode_data(buffer, num_corners)?;
+            scheme.compute_original_values(&mut values, num_components, mesh, point_ids, positions)?;
+        }
+
+        self.portable = values;
+        Ok(())
+    }
+
+    /// Decodes the parameters needed to turn the portable values back into the original ones.
+    pub fn decode_transform_data(&mut self, buffer: &mut DecoderBuffer) -> Result<(), io::Error> {
+        match self.decoder_type {
+            DECODER_QUANTIZATION => {
+                let min_values = (0..self.num_components).map(|_| buffer.read_f32()).collect::<Result<Vec<_>, _>>()?;
+                let range = buffer.read_f32()?;
+                let bits = buffer.read_u8()? as u32;
+                if !(1..=30).contains(&bits) {
+                    return Err(invalid("Invalid Draco quantization bits."));
+                }
+
+                self.quantization = Some((min_values, range, bits));
+            },
+            DECODER_NORMALS => self.octahedron = Some(Octahedron::new(buffer.read_u8()? as u32)?),
+            _ => ()
+        }
+
+        Ok(())
+    }
+
+    pub fn transform_to_original(&mut self) {
+        if self.values.is_some() {
+            return;
+        }
+
+        let values = if let Some((min_values, range, bits)) = &self.quantization {
+            let delta = range / ((1u32 << bits) - 1) as f32;
+            AttributeValues::Float(self.portable.iter().enumerate()
+                .map(|(i, &q)| q as f32 * delta + min_values[i % self.num_components]).collect())
+        } else if let Some(octahedron) = &self.octahedron {
+            AttributeValues::Float(self.portable.chunks_exact(2).flat_map(|st| octahedron.unit_vector(st[0], st[1])).collect())
+        } else {
+            let unsigned = self.data_type == DT_UINT32;
+            AttributeValues::Int(self.portable.iter().map(|&v| if unsigned { v as u32 as i64 } else { v as i64 }).collect())
+        };
+
+        self.values = Some(values);
+    }
+}
+
+fn convert_raw(bytes: &[u8], data_type: u8, size: usize) -> AttributeValues {
+    let le = |c: &[u8]| c.iter().rev().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+    let chunks = bytes.chunks_exact(size);
+
+    match data_type {
+        DT_FLOAT32 => AttributeValues::Float(chunks.map(|c| f32::from_bits(le(c) as u32)).collect()),
+        DT_FLOAT64 => AttributeValues::Float(chunks.map(|c| f64::from_bits(le(c)) as f32).collect()),
+        // Signed types need their sign extended.
+        1 | 3 | 5 | 7 => AttributeValues::Int(chunks.map(|c| ((le(c) << (64 - 8 * size)) as i64) >> (64 - 8 * size)).collect()),
+        _ => AttributeValues::Int(chunks.map(|c| le(c) as i64).collect())
+    }
+}
+
+/// Visits the vertices of `table` the way the encoder did, returning the point of each value.
+pub fn traverse<T: Corners>(table: &T, faces: &[u32], prediction_degree: bool, num_vertices: usize) -> Result<(Vec<u32>, EncodingData), io::Error> {
+    let mut traversal = Traversal {
+        table,
+        faces,
+        face_visited: vec![false; table.num_faces()],
+        vertex_visited: vec![false; table.num_vertices()],
+        point_ids: Vec::new(),
+        data: EncodingData {
+            vertex_to_value: vec![0; num_vertices.max(table.num_vertices())],
+            value_to_corner: Vec::new()
+        },
+        prediction_degree: vec![0; if prediction_degree { table.num_vertices() } else { 0 }]
+    };
+
+    for face in 0..table.num_faces() as u32 {
+        if prediction_degree {
+            traversal.max_prediction_degree(3 * face)?;
+        } else {
+            traversal.depth_first(3 * face)?;
+        }
+    }
+
+    Ok((traversal.point_ids, traversal.data))
+}
+
+struct Traversal<'a, T: Corners> {
+    table:             &'a T,
+    faces:             &'a [u32],
+    face_visited:      Vec<bool>,
+    vertex_visited:    Vec<bool>,
+    point_ids:         Vec<u32>,
+    data:              EncodingData,
+    prediction_degree: Vec<u32>
+}
+
+impl<T: Corners> Traversal<'_, T> {
+    fn is_face_visited(&self, corner: u32) -> bool {
+        corner == INVALID || self.face_visited[(corner / 3) as usize]
+    }
+
+    fn visit_vertex(&mut self, vertex: u32, corner: u32) -> Result<(), io::Error> {
+        let visited = self.vertex_visited.get_mut(vertex as usize).ok_or_else(|| invalid("Corrupt Draco connectivity."))?;
+        if *visited {
+            return Ok(());
+        }
+
+        *visited = true;
+        self.point_ids.push(self.faces[corner as usize]);
+        self.data.vertex_to_value[vertex as usize] = self.data.value_to_corner.len() as i32;
+        self.data.value_to_corner.push(corner);
+        Ok(())
+    }
+
+    fn depth_first(&mut self, corner: u32) -> Result<(), io::Error> {
+        if self.is_face_visited(corner) {
+            return Ok(());
+        }
+
+        let table = self.table;
+        self.visit_vertex(table.vertex(table.next(corner)), table.next(corner))?;
+        self.visit_vertex(table.vertex(table.previous(corner)), table.previous(corner))?;
+
+        let mut stack = vec![corner];
+        while let Some(&top) = stack.last() {
+            let mut corner = top;
+            if self.is_face_visited(corner) {
+                stack.pop();
+                continue;
+            }
+
+            loop {
+                if corner == INVALID {
+                    return Err(invalid("Corrupt Draco connectivity."));
+                }
+
+                self.face_visited[(corner / 3) as usize] = true;
+
+                let vertex = table.vertex(corner);
+                if !self.vertex_visited.get(vertex as usize).copied().unwrap_or(true) {
+                    let on_boundary = table.is_on_boundary(vertex);
+                    self.visit_vertex(vertex, corner)?;
+                    if !on_boundary {
+                        corner = table.right_corner(corner);
+                        continue;
+                    }
+                }
+
+                let right = table.right_corner(corner);
+                let left = table.left_corner(corner);
+                match (self.is_face_visited(right), self.is_face_visited(left)) {
+                    (true, true) => {
+                        stack.pop();
+                        break;
+                    },
+                    (true, false) => corner = left,
+                    (false, true) => corner = right,
+                    (false, false) => {
+                        // Visit the right face first, and come back for the left one.
+                        *stack.last_mut().unwrap() = left;
+                        stack.push(right);
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn max_prediction_degree(&mut self, corner: u32) -> Result<(), io::Error> {
+        const MAX_PRIORITY: usize = 3;
+
+        if self.is_face_visited(corner) {
+            return Ok(());
+        }
+
+        let table = self.table;
+        let mut stacks: [Vec<u32>; MAX_PRIORITY] = Default::default();
+        let mut best_priority = 0;
+        stacks[0].push(corner);
+
+        self.visit_vertex(table.vertex(table.next(corner)), table.next(corner))?;
+        self.visit_vertex(table.vertex(table.previous(corner)), table.previous(corner))?;
+        self.visit_vertex(table.vertex(corner), corner)?;
+
+        while let Some(priority) = (best_priority..MAX_PRIORITY).find(|&p| !stacks[p].is_empty()) {
+            best_priority = priority;
+            let mut corner = stacks[priority].pop().unwrap();
+            if self.is_face_visited(corner) {
+                continue;
+            }
+
+            loop {
+                self.face_visited[(corner / 3) as usize] = true;
+                self.visit_vertex(table.vertex(corner), corner)?;
+
+                let right = table.right_corner(corner);
+                let left = table.left_corner(corner);
+                let right_visited = self.is_face_visited(right);
+
+                if !self.is_face_visited(left) {
+                    let priority = self.priority(left);
+                    if right_visited && priority <= best_priority {
+                        corner = left;
+                        continue;
+                    }
+
+                    stacks[priority].push(left);
+                    best_priority = best_priority.min(priority);
+                }
+
+                if !right_visited {
+                    let priority = self.priority(right);
+                    if priority <= best_priority {
+                        corner = right;
+                        continue;
+                    }
+
+                    stacks[priority].push(right);
+                    best_priority = best_priority.min(priority);
+                }
+
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn priority(&mut self, corner: u32) -> usize {
+        let vertex = self.table.vertex(corner) as usize;
+        if self.vertex_visited.get(vertex).copied().unwrap_or(true) {
+            return 0;
+        }
+
+        self.prediction_degree[vertex] += 1;
+        if self.prediction_degree[vertex] > 1 { 1 } else { 2 }
+    }
+}
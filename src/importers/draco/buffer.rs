@@ -0,0 +1,130 @@
+use std::io;
+
+use super::invalid;
+
+/// A cursor over a Draco bitstream, with the same bit-decoding mode the reference decoder uses.
+#[derive(Clone)]
+pub struct DecoderBuffer<'a> {
+    data:       &'a [u8],
+    position:   usize,
+    bit_mode:   bool,
+    bit_offset: usize
+}
+
+impl<'a> DecoderBuffer<'a> {
+    pub fn new(data: &'a [u8]) -> DecoderBuffer<'a> {
+        DecoderBuffer {
+            data,
+            position: 0,
+            bit_mode: false,
+            bit_offset: 0
+        }
+    }
+
+    /// The bytes that have not been read yet.
+    pub fn head(&self) -> &'a [u8] {
+        &self.data[self.position..]
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    pub fn advance(&mut self, count: usize) -> Result<(), io::Error> {
+        if count > self.remaining() {
+            return Err(invalid("Unexpected end of Draco data."));
+        }
+
+        self.position += count;
+        Ok(())
+    }
+
+    pub fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], io::Error> {
+        let head = self.head();
+        self.advance(count)?;
+        Ok(&head[..count])
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, io::Error> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_i8(&mut self) -> Result<i8, io::Error> {
+        Ok(self.read_u8()? as i8)
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, io::Error> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, io::Error> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, io::Error> {
+        Ok(self.read_u32()? as i32)
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, io::Error> {
+        Ok(f32::from_bits(self.read_u32()?))
+    }
+
+    /// Reads a little-endian base 128 varint.
+    pub fn read_varint(&mut self) -> Result<u64, io::Error> {
+        let mut value = 0u64;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.read_u8()?;
+            if shift >= 64 {
+                return Err(invalid("Draco varint is too long."));
+            }
+
+            value |= ((byte & 0x7F) as u64) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    pub fn read_varint_u32(&mut self) -> Result<u32, io::Error> {
+        u32::try_from(self.read_varint()?).map_err(|_| invalid("Draco varint does not fit in 32 bits."))
+    }
+
+    /// Switches to bit mode, optionally reading the size of the bit data first.
+    pub fn start_bit_decoding(&mut self, decode_size: bool) -> Result<u64, io::Error> {
+        let size = if decode_size { self.read_varint()? } else { 0 };
+
+        self.bit_mode = true;
+        self.bit_offset = 0;
+        Ok(size)
+    }
+
+    /// Reads `count` bits, least significant first. Reading past the end yields zeros, like the reference decoder.
+    pub fn read_bits(&mut self, count: u32) -> Result<u32, io::Error> {
+        if !self.bit_mode {
+            return Err(invalid("Draco bit data read outside of bit mode."));
+        }
+
+        let mut value = 0;
+        for bit in 0..count {
+            let byte = self.position + (self.bit_offset >> 3);
+            if byte < self.data.len() {
+                value |= (((self.data[byte] >> (self.bit_offset & 7)) & 1) as u32) << bit;
+                self.bit_offset += 1;
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Leaves bit mode, skipping every byte that was touched.
+    pub fn end_bit_decoding(&mut self) {
+        self.bit_mode = false;
+        self.position += self.bit_offset.div_ceil(8);
+    }
+}
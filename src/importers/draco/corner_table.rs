@@ -0,0 +1,255 @@
+/// Marks a missing corner or vertex.
+pub const INVALID: u32 = u32::MAX;
+
+/// Connectivity queries shared by the position corner table and the per-attribute corner tables.
+pub trait Corners {
+    fn num_faces(&self) -> usize;
+    fn num_vertices(&self) -> usize;
+    fn vertex(&self, corner: u32) -> u32;
+    fn opposite(&self, corner: u32) -> u32;
+    fn left_most_corner(&self, vertex: u32) -> u32;
+
+    fn next(&self, corner: u32) -> u32 {
+        if corner == INVALID {
+            INVALID
+        } else if corner % 3 == 2 {
+            corner - 2
+        } else {
+            corner + 1
+        }
+    }
+
+    fn previous(&self, corner: u32) -> u32 {
+        if corner == INVALID {
+            INVALID
+        } else if corner.is_multiple_of(3) {
+            corner + 2
+        } else {
+            corner - 1
+        }
+    }
+
+    fn swing_left(&self, corner: u32) -> u32 {
+        self.next(self.opposite(self.next(corner)))
+    }
+
+    fn swing_right(&self, corner: u32) -> u32 {
+        self.previous(self.opposite(self.previous(corner)))
+    }
+
+    /// The corner on the face to the right of `corner`, opposite its next corner.
+    fn right_corner(&self, corner: u32) -> u32 {
+        self.opposite(self.next(corner))
+    }
+
+    fn left_corner(&self, corner: u32) -> u32 {
+        self.opposite(self.previous(corner))
+    }
+
+    fn is_on_boundary(&self, vertex: u32) -> bool {
+        let corner = self.left_most_corner(vertex);
+        corner == INVALID || self.swing_left(corner) == INVALID
+    }
+}
+
+/// Triangle connectivity stored as corners, as rebuilt by the edgebreaker decoder.
+pub struct CornerTable {
+    pub corner_to_vertex: Vec<u32>,
+    pub opposite_corners: Vec<u32>,
+    pub vertex_corners:   Vec<u32>
+}
+
+impl CornerTable {
+    pub fn new(num_faces: usize) -> CornerTable {
+        CornerTable {
+            corner_to_vertex: vec![INVALID; num_faces * 3],
+            opposite_corners: vec![INVALID; num_faces * 3],
+            vertex_corners: Vec::new()
+        }
+    }
+
+    pub fn num_corners(&self) -> usize {
+        self.corner_to_vertex.len()
+    }
+
+    pub fn add_vertex(&mut self) -> u32 {
+        self.vertex_corners.push(INVALID);
+        (self.vertex_corners.len() - 1) as u32
+    }
+
+    pub fn set_opposite(&mut self, a: u32, b: u32) {
+        self.opposite_corners[a as usize] = b;
+        self.opposite_corners[b as usize] = a;
+    }
+
+    pub fn map_corner(&mut self, corner: u32, vertex: u32) {
+        self.corner_to_vertex[corner as usize] = vertex;
+    }
+
+    pub fn set_left_most_corner(&mut self, vertex: u32, corner: u32) {
+        self.vertex_corners[vertex as usize] = corner;
+    }
+}
+
+impl Corners for CornerTable {
+    fn num_faces(&self) -> usize {
+        self.corner_to_vertex.len() / 3
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.vertex_corners.len()
+    }
+
+    fn vertex(&self, corner: u32) -> u32 {
+        if corner == INVALID { INVALID } else { self.corner_to_vertex[corner as usize] }
+    }
+
+    fn opposite(&self, corner: u32) -> u32 {
+        if corner == INVALID { INVALID } else { self.opposite_corners[corner as usize] }
+    }
+
+    fn left_most_corner(&self, vertex: u32) -> u32 {
+        if vertex == INVALID { INVALID } else { self.vertex_corners[vertex as usize] }
+    }
+}
+
+/// The connectivity of a single attribute, where seam edges split the position vertices.
+pub struct AttributeCornerTable<'a> {
+    table:            &'a CornerTable,
+    edge_on_seam:     Vec<bool>,
+    vertex_on_seam:   Vec<bool>,
+    corner_to_vertex: Vec<u32>,
+    vertex_corners:   Vec<u32>
+}
+
+impl<'a> AttributeCornerTable<'a> {
+    pub fn new(table: &'a CornerTable, seams: &[u32]) -> Option<AttributeCornerTable<'a>> {
+        let mut att = AttributeCornerTable {
+            table,
+            edge_on_seam: vec![false; table.num_corners()],
+            vertex_on_seam: vec![false; table.num_vertices()],
+            corner_to_vertex: vec![INVALID; table.num_corners()],
+            vertex_corners: Vec::new()
+        };
+
+        for &corner in seams {
+            att.add_seam_edge(corner);
+        }
+
+        att.recompute_vertices().then_some(att)
+    }
+
+    fn add_seam_edge(&mut self, corner: u32) {
+        let table = self.table;
+        self.edge_on_seam[corner as usize] = true;
+        self.vertex_on_seam[table.vertex(table.next(corner)) as usize] = true;
+        self.vertex_on_seam[table.vertex(table.previous(corner)) as usize] = true;
+
+        let opposite = table.opposite(corner);
+        if opposite != INVALID {
+            self.edge_on_seam[opposite as usize] = true;
+            self.vertex_on_seam[table.vertex(table.next(opposite)) as usize] = true;
+            self.vertex_on_seam[table.vertex(table.previous(opposite)) as usize] = true;
+        }
+    }
+
+    fn recompute_vertices(&mut self) -> bool {
+        let table = self.table;
+        let mut num_vertices = 0;
+
+        for v in 0..table.num_vertices() as u32 {
+            let corner = table.left_most_corner(v);
+            if corner == INVALID {
+                continue;
+            }
+
+            let mut vertex = num_vertices;
+            num_vertices += 1;
+
+            // Walk to the first corner after a seam, so each side of the seam gets its own vertex.
+            let mut first = corner;
+            if self.vertex_on_seam[v as usize] {
+                let mut current = self.swing_left(first);
+                while current != INVALID {
+                    first = current;
+                    current = self.swing_left(current);
+                    if current == corner {
+                        return false;
+                    }
+                }
+            }
+
+            self.corner_to_vertex[first as usize] = vertex;
+            self.vertex_corners.push(first);
+
+            let mut current = table.swing_right(first);
+            while current != INVALID && current != first {
+                if self.edge_on_seam[table.next(current) as usize] {
+                    vertex = num_vertices;
+                    num_vertices += 1;
+                    self.vertex_corners.push(current);
+                }
+
+                self.corner_to_vertex[current as usize] = vertex;
+                current = table.swing_right(current);
+            }
+        }
+
+        true
+    }
+
+    pub fn is_corner_on_seam(&self, corner: u32) -> bool {
+        self.vertex_on_seam[self.table.vertex(corner) as usize]
+    }
+}
+
+impl Corners for AttributeCornerTable<'_> {
+    fn num_faces(&self) -> usize {
+        self.table.num_faces()
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.vertex_corners.len()
+    }
+
+    fn vertex(&self, corner: u32) -> u32 {
+        if corner == INVALID { INVALID } else { self.corner_to_vertex[corner as usize] }
+    }
+
+    fn opposite(&self, corner: u32) -> u32 {
+        if corner == INVALID || self.edge_on_seam[corner as usize] {
+            INVALID
+        } else {
+            self.table.opposite(corner)
+        }
+    }
+
+    fn left_most_corner(&self, vertex: u32) -> u32 {
+        if vertex == INVALID { INVALID } else { self.vertex_corners[vertex as usize] }
+    }
+}
+
+/// The corners around the vertex of `start`, swinging left first and then right from a boundary.
+pub fn vertex_corners<T: Corners>(table: &T, start: u32) -> Vec<u32> {
+    let mut corners = Vec::new();
+    if start == INVALID {
+        return corners;
+    }
+
+    corners.push(start);
+    let mut corner = table.swing_left(start);
+    while corner != INVALID && corner != start {
+        corners.push(corner);
+        corner = table.swing_left(corner);
+    }
+
+    if corner == INVALID {
+        corner = table.swing_right(start);
+        while corner != INVALID && corners.len() <= table.num_faces() * 3 {
+            corners.push(corner);
+            corner = table.swing_right(corner);
+        }
+    }
+
+    corners
+}
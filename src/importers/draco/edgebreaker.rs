@@ -0,0 +1,503 @@
+use std::{io, collections::HashMap};
+
+use super::{
+    buffer::DecoderBuffer,
+    corner_table::{vertex_corners, CornerTable, Corners, INVALID},
+    invalid,
+    rans::{self, RAnsBitDecoder}
+};
+
+const TOPOLOGY_C: u32 = 0;
+const TOPOLOGY_S: u32 = 1;
+const TOPOLOGY_L: u32 = 3;
+const TOPOLOGY_R: u32 = 5;
+const TOPOLOGY_E: u32 = 7;
+
+/// Symbols as they are numbered by the valence coder's contexts.
+const VALENCE_SYMBOLS: [u32; 5] = [TOPOLOGY_C, TOPOLOGY_S, TOPOLOGY_L, TOPOLOGY_R, TOPOLOGY_E];
+
+/// The result of decoding edgebreaker connectivity.
+pub struct Connectivity {
+    pub table:        CornerTable,
+    pub num_vertices: usize,
+    pub is_vert_hole: Vec<bool>,
+    /// The seam corners of each attribute that has its own connectivity.
+    pub seams:        Vec<Vec<u32>>
+}
+
+struct TopologySplit {
+    source_symbol: u32,
+    split_symbol:  u32,
+    /// Whether the split edge is on the right (`true`) or the left of the source face.
+    right_edge:    bool
+}
+
+struct Valence {
+    min:         i32,
+    max:         i32,
+    contexts:    Vec<Vec<u32>>,
+    counters:    Vec<usize>,
+    active:      Option<usize>,
+    last_symbol: u32,
+    valences:    Vec<i32>
+}
+
+impl Valence {
+    fn decode_symbol(&mut self) -> u32 {
+        self.last_symbol = match self.active {
+            Some(context) => {
+                if self.counters[context] == 0 {
+                    return INVALID;
+                }
+
+                self.counters[context] -= 1;
+                match VALENCE_SYMBOLS.get(self.contexts[context][self.counters[context]] as usize) {
+                    Some(&symbol) => symbol,
+                    None => INVALID
+                }
+            },
+            // The first symbol must start a new component.
+            None => TOPOLOGY_E
+        };
+
+        self.last_symbol
+    }
+
+    fn new_active_corner(&mut self, table: &CornerTable, corner: u32) {
+        let next = table.vertex(table.next(corner)) as usize;
+        let prev = table.vertex(table.previous(corner)) as usize;
+        let tip = table.vertex(corner) as usize;
+
+        let increments = match self.last_symbol {
+            TOPOLOGY_C | TOPOLOGY_S => [(next, 1), (prev, 1), (tip, 0)],
+            TOPOLOGY_R => [(tip, 1), (next, 1), (prev, 2)],
+            TOPOLOGY_L => [(tip, 1), (next, 2), (prev, 1)],
+            TOPOLOGY_E => [(tip, 2), (next, 2), (prev, 2)],
+            _ => [(tip, 0), (next, 0), (prev, 0)]
+        };
+
+        for (vertex, increment) in increments {
+            if let Some(valence) = self.valences.get_mut(vertex) {
+                *valence += increment;
+            }
+        }
+
+        let valence = self.valences.get(next).copied().unwrap_or(0).clamp(self.min, self.max);
+        self.active = Some((valence - self.min) as usize);
+    }
+}
+
+struct TraversalDecoder<'a> {
+    symbols:     DecoderBuffer<'a>,
+    start_faces: RAnsBitDecoder<'a>,
+    seams:       Vec<RAnsBitDecoder<'a>>,
+    valence:     Option<Valence>
+}
+
+impl TraversalDecoder<'_> {
+    fn decode_symbol(&mut self) -> Result<u32, io::Error> {
+        if let Some(valence) = &mut self.valence {
+            return Ok(valence.decode_symbol());
+        }
+
+        let symbol = self.symbols.read_bits(1)?;
+        if symbol == TOPOLOGY_C {
+            return Ok(symbol);
+        }
+
+        Ok(symbol | (self.symbols.read_bits(2)? << 1))
+    }
+
+    fn new_active_corner(&mut self, table: &CornerTable, corner: u32) {
+        if let Some(valence) = &mut self.valence {
+            valence.new_active_corner(table, corner);
+        }
+    }
+
+    fn merge_vertices(&mut self, dest: u32, source: u32) {
+        if let Some(valence) = &mut self.valence {
+            let source = valence.valences.get(source as usize).copied().unwrap_or(0);
+            if let Some(dest) = valence.valences.get_mut(dest as usize) {
+                *dest += source;
+            }
+        }
+    }
+}
+
+fn corrupt() -> io::Error {
+    invalid("Corrupt Draco edgebreaker connectivity.")
+}
+
+/// Decodes edgebreaker connectivity, leaving `buffer` at the start of the attribute data.
+pub fn decode(buffer: &mut DecoderBuffer, valence: bool) -> Result<Connectivity, io::Error> {
+    let num_encoded_vertices = buffer.read_varint_u32()? as usize;
+    let num_faces = buffer.read_varint_u32()? as usize;
+    let num_attribute_data = buffer.read_u8()? as usize;
+    let num_symbols = buffer.read_varint_u32()? as usize;
+
+    if num_faces < num_symbols || num_faces > num_symbols + num_symbols / 3 {
+        return Err(corrupt());
+    }
+
+    let num_split_symbols = buffer.read_varint_u32()? as usize;
+    if num_split_symbols > num_symbols {
+        return Err(corrupt());
+    }
+
+    // Every vertex is used by a face, and every split event takes at least two bytes.
+    if num_encoded_vertices > num_faces * 3 {
+        return Err(corrupt());
+    }
+
+    let max_num_vertices = num_encoded_vertices + num_split_symbols;
+
+    // Topology split events, decoded as delta coded symbol ids followed by one bit per split edge.
+    let num_splits = buffer.read_varint_u32()? as usize;
+    if num_splits > num_faces || num_splits > buffer.remaining() / 2 {
+        return Err(corrupt());
+    }
+
+    let mut splits = Vec::with_capacity(num_splits);
+    let mut last_source = 0u32;
+    for _ in 0..num_splits {
+        let source_symbol = buffer.read_varint_u32()?.checked_add(last_source).ok_or_else(corrupt)?;
+        let delta = buffer.read_varint_u32()?;
+        if delta > source_symbol {
+            return Err(corrupt());
+        }
+
+        splits.push(TopologySplit { source_symbol, split_symbol: source_symbol - delta, right_edge: false });
+        last_source = source_symbol;
+    }
+
+    buffer.start_bit_decoding(false)?;
+    for split in &mut splits {
+        split.right_edge = buffer.read_bits(1)? == 1;
+    }
+    buffer.end_bit_decoding();
+
+    let mut traversal = buffer.clone();
+    let mut symbols = traversal.clone();
+    if !valence {
+        // Every symbol takes at least one bit.
+        let size = traversal.read_varint()?;
+        if size > traversal.remaining() as u64 || num_symbols as u64 > size * 8 {
+            return Err(corrupt());
+        }
+
+        symbols = traversal.clone();
+        symbols.start_bit_decoding(false)?;
+        traversal.advance(size as usize)?;
+    }
+
+    let start_faces = RAnsBitDecoder::start(&mut traversal)?;
+    let seams = (0..num_attribute_data).map(|_| RAnsBitDecoder::start(&mut traversal)).collect::<Result<Vec<_>, _>>()?;
+
+    let valence = if valence {
+        let min = traversal.read_varint_u32()? as i32;
+        let max = traversal.read_varint_u32()? as i32;
+        if min < 0 || max < min {
+            return Err(corrupt());
+        }
+
+        let mut contexts = Vec::new();
+        for _ in min..=max {
+            let count = traversal.read_varint_u32()? as usize;
+            if count > num_faces {
+                return Err(corrupt());
+            }
+
+            contexts.push(rans::decode_symbols(&mut traversal, count, 1)?);
+        }
+
+        Some(Valence {
+            min,
+            max,
+            counters: contexts.iter().map(|c| c.len()).collect(),
+            contexts,
+            active: None,
+            last_symbol: INVALID,
+            valences: vec![0; max_num_vertices]
+        })
+    } else {
+        None
+    };
+
+    let mut traversal_decoder = TraversalDecoder {
+        symbols,
+        start_faces,
+        seams,
+        valence
+    };
+    *buffer = traversal;
+
+    let mut table = CornerTable::new(num_faces);
+    let mut is_vert_hole = vec![true; max_num_vertices];
+
+    let remove_invalid_vertices = num_attribute_data == 0;
+    let mut invalid_vertices = Vec::new();
+    let mut active_corners: Vec<u32> = Vec::new();
+    let mut split_corners: HashMap<usize, u32> = HashMap::new();
+    let mut num_decoded_faces = 0;
+
+    for symbol_id in 0..num_symbols {
+        let corner = 3 * num_decoded_faces as u32;
+        num_decoded_faces += 1;
+        let mut check_topology_split = false;
+
+        match traversal_decoder.decode_symbol()? {
+            TOPOLOGY_C => {
+                // A new face closing the gap between the active edge and the edge next to it around vertex x.
+                let corner_a = *active_corners.last().ok_or_else(corrupt)?;
+                let vertex_x = table.vertex(table.next(corner_a));
+                let corner_b = table.next(table.left_most_corner(vertex_x));
+
+                if corner_b == INVALID || corner_a == corner_b || table.opposite(corner_a) != INVALID || table.opposite(corner_b) != INVALID {
+                    return Err(corrupt());
+                }
+
+                table.set_opposite(corner_a, corner + 1);
+                table.set_opposite(corner_b, corner + 2);
+
+                let vert_a_prev = table.vertex(table.previous(corner_a));
+                let vert_b_next = table.vertex(table.next(corner_b));
+                if vertex_x == vert_a_prev || vertex_x == vert_b_next {
+                    return Err(corrupt());
+                }
+
+                table.map_corner(corner, vertex_x);
+                table.map_corner(corner + 1, vert_b_next);
+                table.map_corner(corner + 2, vert_a_prev);
+                table.set_left_most_corner(vert_a_prev, corner + 2);
+
+                is_vert_hole[vertex_x as usize] = false;
+                *active_corners.last_mut().unwrap() = corner;
+            },
+            symbol @ (TOPOLOGY_R | TOPOLOGY_L) => {
+                // A new face with one new vertex, attached to the active edge.
+                let corner_a = *active_corners.last().ok_or_else(corrupt)?;
+                if table.opposite(corner_a) != INVALID {
+                    return Err(corrupt());
+                }
+
+                let (opp_corner, corner_l, corner_r) = if symbol == TOPOLOGY_R {
+                    (corner + 2, corner + 1, corner)
+                } else {
+                    (corner + 1, corner, corner + 2)
+                };
+
+                table.set_opposite(opp_corner, corner_a);
+
+                let new_vertex = table.add_vertex();
+                if table.num_vertices() > max_num_vertices {
+                    return Err(corrupt());
+                }
+
+                table.map_corner(opp_corner, new_vertex);
+                table.set_left_most_corner(new_vertex, opp_corner);
+
+                let vertex_r = table.vertex(table.previous(corner_a));
+                table.map_corner(corner_r, vertex_r);
+                table.set_left_most_corner(vertex_r, corner_r);
+                table.map_corner(corner_l, table.vertex(table.next(corner_a)));
+
+                *active_corners.last_mut().unwrap() = corner;
+                check_topology_split = true;
+            },
+            TOPOLOGY_S => {
+                // A new face merging the two topmost active edges, which also merges vertices p and n.
+                let corner_b = active_corners.pop().ok_or_else(corrupt)?;
+                if let Some(&split) = split_corners.get(&symbol_id) {
+                    active_corners.push(split);
+                }
+
+                let corner_a = *active_corners.last().ok_or_else(corrupt)?;
+                if corner_a == corner_b || table.opposite(corner_a) != INVALID || table.opposite(corner_b) != INVALID {
+                    return Err(corrupt());
+                }
+
+                table.set_opposite(corner_a, corner + 2);
+                table.set_opposite(corner_b, corner + 1);
+
+                let vertex_p = table.vertex(table.previous(corner_a));
+                table.map_corner(corner, vertex_p);
+                table.map_corner(corner + 1, table.vertex(table.next(corner_a)));
+
+                let vert_b_prev = table.vertex(table.previous(corner_b));
+                table.map_corner(corner + 2, vert_b_prev);
+                table.set_left_most_corner(vert_b_prev, corner + 2);
+
+                let mut corner_n = table.next(corner_b);
+                let vertex_n = table.vertex(corner_n);
+                traversal_decoder.merge_vertices(vertex_p, vertex_n);
+                table.set_left_most_corner(vertex_p, table.left_most_corner(vertex_n));
+
+                let first_corner = corner_n;
+                while corner_n != INVALID {
+                    table.map_corner(corner_n, vertex_p);
+                    corner_n = table.swing_left(corner_n);
+                    if corner_n == first_corner {
+                        return Err(corrupt());
+                    }
+                }
+
+                table.set_left_most_corner(vertex_n, INVALID);
+                if remove_invalid_vertices {
+                    invalid_vertices.push(vertex_n);
+                }
+
+                *active_corners.last_mut().unwrap() = corner;
+            },
+            TOPOLOGY_E => {
+                // A new face with three new vertices, starting a new active edge.
+                for i in 0..3 {
+                    let vertex = table.add_vertex();
+                    table.map_corner(corner + i, vertex);
+                    if table.num_vertices() > max_num_vertices {
+                        return Err(corrupt());
+                    }
+
+                    table.set_left_most_corner(vertex, corner + i);
+                }
+
+                active_corners.push(corner);
+                check_topology_split = true;
+            },
+            _ => return Err(corrupt())
+        }
+
+        traversal_decoder.new_active_corner(&table, *active_corners.last().unwrap());
+
+        if check_topology_split {
+            // The encoder numbers its symbols in the reverse order.
+            let encoder_symbol_id = (num_symbols - symbol_id - 1) as u32;
+
+            while let Some(split) = splits.last() {
+                if split.source_symbol > encoder_symbol_id {
+                    return Err(corrupt());
+                }
+
+                if split.source_symbol != encoder_symbol_id {
+                    break;
+                }
+
+                let top = *active_corners.last().unwrap();
+                let new_active_corner = if split.right_edge { table.next(top) } else { table.previous(top) };
+                let decoder_split_symbol = num_symbols - split.split_symbol as usize - 1;
+                split_corners.insert(decoder_split_symbol, new_active_corner);
+                splits.pop();
+            }
+        }
+    }
+
+    if table.num_vertices() > max_num_vertices {
+        return Err(corrupt());
+    }
+
+    // The remaining active edges are closed by start faces, which can be interior or lie on a boundary.
+    while let Some(corner) = active_corners.pop() {
+        if !traversal_decoder.start_faces.decode_bit() {
+            continue;
+        }
+
+        if num_decoded_faces >= table.num_faces() {
+            return Err(corrupt());
+        }
+
+        let vert_n = table.vertex(table.next(corner));
+        let corner_b = table.next(table.left_most_corner(vert_n));
+        let vert_x = table.vertex(table.next(corner_b));
+        let corner_c = table.next(table.left_most_corner(vert_x));
+
+        if corner_b == INVALID || corner_c == INVALID || corner == corner_b || corner == corner_c || corner_b == corner_c {
+            return Err(corrupt());
+        }
+
+        if table.opposite(corner) != INVALID || table.opposite(corner_b) != INVALID || table.opposite(corner_c) != INVALID {
+            return Err(corrupt());
+        }
+
+        let vert_p = table.vertex(table.next(corner_c));
+        let new_corner = 3 * num_decoded_faces as u32;
+        num_decoded_faces += 1;
+
+        table.set_opposite(new_corner, corner);
+        table.set_opposite(new_corner + 1, corner_b);
+        table.set_opposite(new_corner + 2, corner_c);
+
+        table.map_corner(new_corner, vert_x);
+        table.map_corner(new_corner + 1, vert_p);
+        table.map_corner(new_corner + 2, vert_n);
+
+        for vertex in [vert_x, vert_p, vert_n] {
+            is_vert_hole[vertex as usize] = false;
+        }
+    }
+
+    if num_decoded_faces != table.num_faces() {
+        return Err(corrupt());
+    }
+
+    // Move the last valid vertices into the holes left by merged vertices, so all vertices are in use.
+    let mut num_vertices = table.num_vertices();
+    for invalid_vertex in invalid_vertices {
+        let mut source = num_vertices.checked_sub(1).ok_or_else(corrupt)? as u32;
+        while table.left_most_corner(source) == INVALID {
+            num_vertices -= 1;
+            source = num_vertices.checked_sub(1).ok_or_else(corrupt)? as u32;
+        }
+
+        if source < invalid_vertex {
+            continue;
+        }
+
+        for corner in vertex_corners(&table, table.left_most_corner(source)) {
+            if table.vertex(corner) != source {
+                return Err(corrupt());
+            }
+
+            table.map_corner(corner, invalid_vertex);
+        }
+
+        table.set_left_most_corner(invalid_vertex, table.left_most_corner(source));
+        table.set_left_most_corner(source, INVALID);
+        is_vert_hole[invalid_vertex as usize] = is_vert_hole[source as usize];
+        is_vert_hole[source as usize] = false;
+        num_vertices -= 1;
+    }
+
+    // Attribute seams are coded per edge, once for each pair of faces; boundary edges are always seams.
+    let mut seams = vec![Vec::new(); num_attribute_data];
+    if num_attribute_data > 0 {
+        for face in 0..table.num_faces() as u32 {
+            let corner = 3 * face;
+            for c in [corner, table.next(corner), table.previous(corner)] {
+                let opposite = table.opposite(c);
+                if opposite == INVALID {
+                    for seam in &mut seams {
+                        seam.push(c);
+                    }
+
+                    continue;
+                }
+
+                if opposite / 3 < face {
+                    continue;
+                }
+
+                for (i, seam) in seams.iter_mut().enumerate() {
+                    if traversal_decoder.seams[i].decode_bit() {
+                        seam.push(c);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Connectivity {
+        table,
+        num_vertices,
+        is_vert_hole,
+        seams
+    })
+}
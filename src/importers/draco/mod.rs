@@ -0,0 +1,451 @@
+//! A decoder for Draco compressed meshes, as used by `KHR_draco_mesh_compression`.
+//!
+//! This follows the reference decoder for version 2.2 bitstreams, which is what current encoders write.
+
+use std::io;
+
+use self::{
+    attributes::{Attribute, EncodingData, ATTRIBUTE_POSITION},
+    buffer::DecoderBuffer,
+    corner_table::{AttributeCornerTable, CornerTable, Corners, INVALID},
+    prediction::{MeshData, Positions}
+};
+
+pub use self::attributes::AttributeValues;
+
+mod attributes;
+mod buffer;
+mod corner_table;
+mod edgebreaker;
+mod prediction;
+mod rans;
+
+const METADATA_FLAG: u16 = 0x8000;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn unsupported(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, message)
+}
+
+/// A decoded attribute, with one value per point.
+pub struct DracoAttribute {
+    /// The id glTF uses to refer to this attribute.
+    pub unique_id:      u32,
+    pub num_components: usize,
+    pub values:         AttributeValues
+}
+
+/// A decoded Draco mesh. The indices refer to points, which every attribute has a value for.
+pub struct DracoMesh {
+    pub indices:    Vec<u32>,
+    pub num_points: usize,
+    pub attributes: Vec<DracoAttribute>
+}
+
+/// Decodes a Draco compressed triangle mesh.
+pub fn decode(data: &[u8]) -> Result<DracoMesh, io::Error> {
+    let mut buffer = DecoderBuffer::new(data);
+
+    if buffer.read_bytes(5).ok() != Some(b"DRACO".as_slice()) {
+        return Err(invalid("Draco data does not start with the Draco magic."));
+    }
+
+    let (major, minor) = (buffer.read_u8()?, buffer.read_u8()?);
+    if (major, minor) != (2, 2) {
+        return Err(unsupported(&format!("Draco bitstream version {major}.{minor} is not supported.")));
+    }
+
+    let encoder_type = buffer.read_u8()?;
+    if encoder_type != 1 {
+        return Err(unsupported("Only Draco triangle meshes are supported."));
+    }
+
+    let method = buffer.read_u8()?;
+    let flags = buffer.read_u16()?;
+    if flags & METADATA_FLAG != 0 {
+        skip_metadata(&mut buffer)?;
+    }
+
+    match method {
+        0 => decode_sequential(&mut buffer),
+        1 => decode_edgebreaker(&mut buffer),
+        _ => Err(invalid(&format!("Unrecognized Draco encoding method {method}.")))
+    }
+}
+
+fn skip_metadata(buffer: &mut DecoderBuffer) -> Result<(), io::Error> {
+    fn skip_element(buffer: &mut DecoderBuffer, depth: u32) -> Result<(), io::Error> {
+        if depth > 32 {
+            return Err(invalid("Draco metadata is nested too deeply."));
+        }
+
+        for _ in 0..buffer.read_varint_u32()? {
+            let key_size = buffer.read_u8()? as usize;
+            buffer.advance(key_size)?;
+            let value_size = buffer.read_u8()? as usize;
+            buffer.advance(value_size)?;
+        }
+
+        for _ in 0..buffer.read_varint_u32()? {
+            let key_size = buffer.read_u8()? as usize;
+            buffer.advance(key_size)?;
+            skip_element(buffer, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    for _ in 0..buffer.read_varint_u32()? {
+        buffer.read_varint_u32()?;
+        skip_element(buffer, 0)?;
+    }
+
+    skip_element(buffer, 0)
+}
+
+fn decode_sequential(buffer: &mut DecoderBuffer) -> Result<DracoMesh, io::Error> {
+    let num_faces = buffer.read_varint_u32()? as usize;
+    let num_points = buffer.read_varint_u32()? as usize;
+    let num_indices = num_faces.checked_mul(3).filter(|&n| n <= buffer.remaining() * 8).ok_or_else(|| invalid("Too many Draco faces."))?;
+
+    // Like the faces, every point takes at least a bit to encode.
+    if num_points > buffer.remaining() * 8 {
+        return Err(invalid("Too many Draco points."));
+    }
+
+    let indices = match buffer.read_u8()? {
+        // Compressed: the indices are delta coded.
+        0 => {
+            let mut last = 0i64;
+            let mut indices = Vec::with_capacity(num_indices);
+            for value in rans::decode_symbols(buffer, num_indices, 1)? {
+                let delta = (value >> 1) as i64;
+                last += if value & 1 == 1 { -delta } else { delta };
+                indices.push(last as u32);
+            }
+
+            indices
+        },
+        1 => {
+            let mut indices = Vec::with_capacity(num_indices);
+            for _ in 0..num_indices {
+                indices.push(if num_points < 256 {
+                    buffer.read_u8()? as u32
+                } else if num_points < 1 << 16 {
+                    buffer.read_u16()? as u32
+                } else if num_points < 1 << 21 {
+                    buffer.read_varint_u32()?
+                } else {
+                    buffer.read_u32()?
+                });
+            }
+
+            indices
+        },
+        method => return Err(invalid(&format!("Unrecognized Draco connectivity method {method}.")))
+    };
+
+    if indices.iter().any(|&i| i as usize >= num_points) {
+        return Err(invalid("Draco index is out of range."));
+    }
+
+    let num_decoders = buffer.read_u8()? as usize;
+    let mut decoders = Vec::with_capacity(num_decoders);
+    for _ in 0..num_decoders {
+        decoders.push(decode_attribute_headers(buffer)?);
+    }
+
+    // Points are stored in order, so every attribute maps points to values directly.
+    let point_ids = (0..num_points as u32).collect::<Vec<_>>();
+    let mut attributes = Vec::new();
+    for decoder in decoders {
+        let first = attributes.len();
+        attributes.extend(decoder);
+
+        for attribute in &mut attributes[first..] {
+            attribute.point_to_value = point_ids.clone();
+        }
+
+        decode_attributes::<CornerTable>(buffer, &mut attributes, first, &point_ids, None, indices.len())?;
+    }
+
+    finish(indices, num_points, attributes)
+}
+
+struct AttributesDecoder {
+    data_id:           i8,
+    per_corner:        bool,
+    prediction_degree: bool
+}
+
+fn decode_edgebreaker(buffer: &mut DecoderBuffer) -> Result<DracoMesh, io::Error> {
+    let valence = match buffer.read_u8()? {
+        0 => false,
+        2 => true,
+        1 => return Err(unsupported("The predictive Draco edgebreaker is not supported.")),
+        traversal => return Err(invalid(&format!("Unrecognized Draco edgebreaker traversal {traversal}.")))
+    };
+
+    let connectivity = edgebreaker::decode(buffer, valence)?;
+    let table = &connectivity.table;
+
+    let attribute_tables = connectivity.seams.iter()
+        .map(|seams| AttributeCornerTable::new(table, seams).ok_or_else(|| invalid("Corrupt Draco attribute seams.")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (indices, num_points) = assign_points(table, &connectivity.is_vert_hole, &attribute_tables, connectivity.num_vertices)?;
+
+    let num_decoders = buffer.read_u8()? as usize;
+    let mut decoders = Vec::with_capacity(num_decoders);
+    let mut has_position_decoder = false;
+    for _ in 0..num_decoders {
+        let data_id = buffer.read_i8()?;
+        let per_corner = match buffer.read_u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(invalid("Unrecognized Draco attribute decoder type."))
+        };
+
+        let prediction_degree = match buffer.read_u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(invalid("Unrecognized Draco traversal method."))
+        };
+
+        if data_id >= 0 && data_id as usize >= attribute_tables.len() {
+            return Err(invalid("Draco attribute data is out of range."));
+        }
+
+        if data_id < 0 {
+            if has_position_decoder {
+                return Err(invalid("Multiple Draco attribute decoders use the position connectivity."));
+            }
+
+            has_position_decoder = true;
+        }
+
+        if per_corner && (data_id < 0 || prediction_degree) {
+            return Err(invalid("Invalid Draco per-corner attribute decoder."));
+        }
+
+        decoders.push(AttributesDecoder { data_id, per_corner, prediction_degree });
+    }
+
+    let mut headers = Vec::with_capacity(num_decoders);
+    for _ in 0..num_decoders {
+        headers.push(decode_attribute_headers(buffer)?);
+    }
+
+    let mut attributes = Vec::new();
+    for (decoder, header) in decoders.iter().zip(headers) {
+        let first = attributes.len();
+        attributes.extend(header);
+
+        if decoder.per_corner {
+            let att_table = &attribute_tables[decoder.data_id as usize];
+            let num_vertices = att_table.num_vertices().max(table.num_vertices());
+            let (point_ids, data) = attributes::traverse(att_table, &indices, false, num_vertices)?;
+
+            map_points(att_table, &indices, num_points, &data, &mut attributes[first..])?;
+            decode_attributes(buffer, &mut attributes, first, &point_ids, Some(&mesh_data(att_table, &data)), indices.len())?;
+        } else {
+            let num_vertices = match decoder.data_id {
+                id if id < 0 => table.num_vertices(),
+                id => attribute_tables[id as usize].num_vertices().max(table.num_vertices())
+            };
+
+            let (point_ids, data) = attributes::traverse(table, &indices, decoder.prediction_degree, num_vertices)?;
+
+            map_points(table, &indices, num_points, &data, &mut attributes[first..])?;
+            decode_attributes(buffer, &mut attributes, first, &point_ids, Some(&mesh_data(table, &data)), indices.len())?;
+        }
+    }
+
+    finish(indices, num_points, attributes)
+}
+
+fn mesh_data<'a, T: Corners>(table: &'a T, data: &'a EncodingData) -> MeshData<'a, T> {
+    MeshData {
+        table,
+        vertex_to_value: &data.vertex_to_value,
+        value_to_corner: &data.value_to_corner
+    }
+}
+
+/// Splits vertices into points wherever any attribute has a seam, and returns the faces in points.
+fn assign_points(table: &CornerTable, is_vert_hole: &[bool], attribute_tables: &[AttributeCornerTable], num_vertices: usize) -> Result<(Vec<u32>, usize), io::Error> {
+    if attribute_tables.is_empty() {
+        if table.corner_to_vertex.iter().any(|&v| v as usize >= num_vertices) {
+            return Err(invalid("Corrupt Draco connectivity."));
+        }
+
+        return Ok((table.corner_to_vertex.clone(), num_vertices));
+    }
+
+    let mut corner_to_point = vec![INVALID; table.num_corners()];
+    let mut num_points = 0;
+
+    for v in 0..table.num_vertices() as u32 {
+        let corner = table.left_most_corner(v);
+        if corner == INVALID {
+            continue;
+        }
+
+        // Interior vertices start at the first seam of any attribute; boundary vertices at their boundary.
+        let mut first = corner;
+        if !is_vert_hole[v as usize] {
+            for att_table in attribute_tables {
+                if !att_table.is_corner_on_seam(corner) {
+                    continue;
+                }
+
+                let vertex = att_table.vertex(corner);
+                let mut current = table.swing_right(corner);
+                let mut seam_found = false;
+                while current != corner {
+                    if current == INVALID {
+                        return Err(invalid("Corrupt Draco connectivity."));
+                    }
+
+                    if att_table.vertex(current) != vertex {
+                        first = current;
+                        seam_found = true;
+                        break;
+                    }
+
+                    current = table.swing_right(current);
+                }
+
+                if seam_found {
+                    break;
+                }
+            }
+        }
+
+        corner_to_point[first as usize] = num_points;
+        num_points += 1;
+
+        let mut previous = first;
+        let mut current = table.swing_right(first);
+        while current != INVALID && current != first {
+            if attribute_tables.iter().any(|att| att.vertex(current) != att.vertex(previous)) {
+                corner_to_point[current as usize] = num_points;
+                num_points += 1;
+            } else {
+                corner_to_point[current as usize] = corner_to_point[previous as usize];
+            }
+
+            previous = current;
+            current = table.swing_right(current);
+        }
+    }
+
+    if corner_to_point.contains(&INVALID) {
+        return Err(invalid("Corrupt Draco connectivity."));
+    }
+
+    Ok((corner_to_point, num_points as usize))
+}
+
+fn decode_attribute_headers(buffer: &mut DecoderBuffer) -> Result<Vec<Attribute>, io::Error> {
+    let num_attributes = buffer.read_varint_u32()? as usize;
+    if num_attributes == 0 || num_attributes > buffer.remaining() {
+        return Err(invalid("Invalid number of Draco attributes."));
+    }
+
+    let mut attributes = (0..num_attributes).map(|_| Attribute::decode_header(buffer)).collect::<Result<Vec<_>, _>>()?;
+    for attribute in &mut attributes {
+        attribute.decoder_type = buffer.read_u8()?;
+    }
+
+    Ok(attributes)
+}
+
+/// Maps every point to the value its corner's vertex was given during the traversal.
+fn map_points<T: Corners>(table: &T, indices: &[u32], num_points: usize, data: &EncodingData, attributes: &mut [Attribute]) -> Result<(), io::Error> {
+    let mut point_to_value = vec![0; num_points];
+    for (corner, &point) in indices.iter().enumerate() {
+        let value = table.vertex(corner as u32) as usize;
+        let value = *data.vertex_to_value.get(value).ok_or_else(|| invalid("Corrupt Draco connectivity."))?;
+        if value < 0 || value as usize >= num_points {
+            return Err(invalid("Corrupt Draco connectivity."));
+        }
+
+        point_to_value[point as usize] = value as u32;
+    }
+
+    for attribute in attributes {
+        attribute.point_to_value = point_to_value.clone();
+    }
+
+    Ok(())
+}
+
+/// Decodes the attributes from `first` on, which all share one decoder and traversal.
+fn decode_attributes<T: Corners>(buffer: &mut DecoderBuffer, attributes: &mut [Attribute], first: usize, point_ids: &[u32],
+                                 mesh: Option<&MeshData<T>>, num_corners: usize) -> Result<(), io::Error> {
+    let position = attributes.iter().position(|a| a.attribute_type == ATTRIBUTE_POSITION);
+
+    for i in first..attributes.len() {
+        let (decoded, rest) = attributes.split_at_mut(i);
+        let positions = position.filter(|&p| p < i).map(|p| Positions {
+            values: &decoded[p].portable,
+            point_to_value: &decoded[p].point_to_value
+        });
+
+        rest[0].decode_values(buffer, point_ids, mesh, positions.as_ref(), num_corners)?;
+    }
+
+    for attribute in &mut attributes[first..] {
+        attribute.decode_transform_data(buffer)?;
+    }
+
+    for attribute in &mut attributes[first..] {
+        attribute.transform_to_original();
+    }
+
+    Ok(())
+}
+
+/// Expands the attribute values so there is one for every point.
+fn finish(indices: Vec<u32>, num_points: usize, attributes: Vec<Attribute>) -> Result<DracoMesh, io::Error> {
+    let mut decoded = Vec::with_capacity(attributes.len());
+
+    for attribute in attributes {
+        let n = attribute.num_components;
+        let num_values = match &attribute.values {
+            Some(AttributeValues::Float(v)) => v.len() / n,
+            Some(AttributeValues::Int(v)) => v.len() / n,
+            None => 0
+        };
+
+        if attribute.point_to_value.iter().any(|&v| v as usize >= num_values) {
+            return Err(invalid("Draco attribute has fewer values than points."));
+        }
+
+        let values = match &attribute.values {
+            Some(AttributeValues::Float(v)) => AttributeValues::Float(expand(v, &attribute.point_to_value, n)),
+            Some(AttributeValues::Int(v)) => AttributeValues::Int(expand(v, &attribute.point_to_value, n)),
+            None => AttributeValues::Float(Vec::new())
+        };
+
+        decoded.push(DracoAttribute {
+            unique_id: attribute.unique_id,
+            num_components: n,
+            values
+        });
+    }
+
+    Ok(DracoMesh {
+        indices,
+        num_points,
+        attributes: decoded
+    })
+}
+
+fn expand<T: Copy>(values: &[T], point_to_value: &[u32], num_components: usize) -> Vec<T> {
+    point_to_value.iter().flat_map(|&v| &values[v as usize * num_components..(v as usize + 1) * num_components]).copied().collect()
+}
@@ -0,0 +1,672 @@
+use std::io;
+
+use super::{
+    buffer::DecoderBuffer,
+    corner_table::{vertex_corners, Corners, INVALID},
+    invalid,
+    rans::RAnsBitDecoder
+};
+
+pub const PREDICTION_NONE: i8 = -2;
+
+const TRANSFORM_WRAP: i8 = 1;
+const TRANSFORM_NORMAL_OCTAHEDRON_CANONICALIZED: i8 = 3;
+
+const MAX_NUM_PARALLELOGRAMS: usize = 4;
+
+/// The connectivity a mesh prediction scheme walks, and how its values map onto it.
+pub struct MeshData<'a, T: Corners> {
+    pub table:           &'a T,
+    pub vertex_to_value: &'a [i32],
+    pub value_to_corner: &'a [u32]
+}
+
+/// The portable (quantized) positions some prediction schemes predict from.
+pub struct Positions<'a> {
+    pub values:         &'a [i32],
+    pub point_to_value: &'a [u32]
+}
+
+impl Positions<'_> {
+    fn get(&self, point: u32) -> Option<[i64; 3]> {
+        let value = *self.point_to_value.get(point as usize)? as usize;
+        let position = self.values.get(value * 3..value * 3 + 3)?;
+
+        Some([position[0] as i64, position[1] as i64, position[2] as i64])
+    }
+}
+
+/// Quantization parameters for octahedral normal coordinates.
+#[derive(Clone, Copy)]
+pub struct Octahedron {
+    pub max_quantized_value: i32,
+    pub max_value:           i32,
+    pub center_value:        i32
+}
+
+impl Octahedron {
+    pub fn new(quantization_bits: u32) -> Result<Octahedron, io::Error> {
+        if !(2..=30).contains(&quantization_bits) {
+            return Err(invalid("Invalid Draco normal quantization bits."));
+        }
+
+        let max_quantized_value = (1 << quantization_bits) - 1;
+        let max_value = max_quantized_value - 1;
+
+        Ok(Octahedron {
+            max_quantized_value,
+            max_value,
+            center_value: max_value / 2
+        })
+    }
+
+    /// Converts quantized octahedral coordinates back into a unit vector.
+    pub fn unit_vector(&self, s: i32, t: i32) -> [f32; 3] {
+        let scale = 2.0 / self.max_value as f32;
+        let mut y = s as f32 * scale - 1.0;
+        let mut z = t as f32 * scale - 1.0;
+        let x = 1.0 - y.abs() - z.abs();
+
+        // Unfold the lower half of the octahedron.
+        let x_offset = (-x).max(0.0);
+        y += if y < 0.0 { x_offset } else { -x_offset };
+        z += if z < 0.0 { x_offset } else { -x_offset };
+
+        let norm_squared = x * x + y * y + z * z;
+        if norm_squared < 1e-6 {
+            [0.0; 3]
+        } else {
+            let d = 1.0 / norm_squared.sqrt();
+            [x * d, y * d, z * d]
+        }
+    }
+
+    fn canonicalize_vector(&self, vector: &mut [i64; 3]) {
+        let abs_sum = vector[0].abs() + vector[1].abs() + vector[2].abs();
+        let center = self.center_value as i64;
+
+        if abs_sum == 0 {
+            vector[0] = center;
+        } else {
+            vector[0] = vector[0] * center / abs_sum;
+            vector[1] = vector[1] * center / abs_sum;
+
+            let z = center - vector[0].abs() - vector[1].abs();
+            vector[2] = if vector[2] >= 0 { z } else { -z };
+        }
+    }
+
+    fn vector_to_coords(&self, vector: &[i64; 3]) -> [i32; 2] {
+        let (center, max) = (self.center_value as i64, self.max_value as i64);
+        let (s, t) = if vector[0] >= 0 {
+            (vector[1] + center, vector[2] + center)
+        } else {
+            (
+                if vector[1] < 0 { vector[2].abs() } else { max - vector[2].abs() },
+                if vector[2] < 0 { vector[1].abs() } else { max - vector[1].abs() }
+            )
+        };
+
+        let (mut s, mut t) = (s as i32, t as i32);
+        let (center, max) = (self.center_value, self.max_value);
+        if (s == 0 && (t == 0 || t == max)) || (s == max && t == 0) {
+            s = max;
+            t = max;
+        } else if s == 0 && t > center {
+            t = center - (t - center);
+        } else if s == max && t < center {
+            t = center + (center - t);
+        } else if t == max && s < center {
+            s = center + (center - s);
+        } else if t == 0 && s > center {
+            s = center - (s - center);
+        }
+
+        [s, t]
+    }
+
+    fn invert_diamond(&self, [s, t]: [i32; 2]) -> [i32; 2] {
+        let (sign_s, sign_t) = if s >= 0 && t >= 0 {
+            (1, 1)
+        } else if s <= 0 && t <= 0 {
+            (-1, -1)
+        } else {
+            (if s > 0 { 1 } else { -1 }, if t > 0 { 1 } else { -1 })
+        };
+
+        let corner_s = sign_s * self.center_value;
+        let corner_t = sign_t * self.center_value;
+        let (s, t) = (2 * s - corner_s, 2 * t - corner_t);
+        let (s, t) = if sign_s * sign_t >= 0 { (-t, -s) } else { (t, s) };
+
+        [(s + corner_s) / 2, (t + corner_t) / 2]
+    }
+
+    fn mod_max(&self, x: i32) -> i32 {
+        if x > self.center_value {
+            x - self.max_quantized_value
+        } else if x < -self.center_value {
+            x + self.max_quantized_value
+        } else {
+            x
+        }
+    }
+
+    /// Reverts the canonicalized octahedral correction of a single normal.
+    fn original_value(&self, pred: [i32; 2], corr: [i32; 2]) -> [i32; 2] {
+        let center = self.center_value;
+        let mut pred = [pred[0] - center, pred[1] - center];
+
+        let in_diamond = pred[0].abs() + pred[1].abs() <= center;
+        if !in_diamond {
+            pred = self.invert_diamond(pred);
+        }
+
+        let in_bottom_left = (pred[0] == 0 && pred[1] == 0) || (pred[0] < 0 && pred[1] <= 0);
+        let rotation = match (pred[0].signum(), pred[1]) {
+            (0, 0) => 0,
+            (0, y) if y > 0 => 3,
+            (0, _) => 1,
+            (1, y) if y >= 0 => 2,
+            (1, _) => 1,
+            (_, y) if y <= 0 => 0,
+            _ => 3
+        };
+
+        if !in_bottom_left {
+            pred = rotate(pred, rotation);
+        }
+
+        let mut orig = [self.mod_max(pred[0].wrapping_add(corr[0])), self.mod_max(pred[1].wrapping_add(corr[1]))];
+        if !in_bottom_left {
+            orig = rotate(orig, (4 - rotation) % 4);
+        }
+
+        if !in_diamond {
+            orig = self.invert_diamond(orig);
+        }
+
+        [orig[0] + center, orig[1] + center]
+    }
+}
+
+fn rotate([s, t]: [i32; 2], count: u32) -> [i32; 2] {
+    match count {
+        1 => [t, -s],
+        2 => [-s, -t],
+        3 => [-t, s],
+        _ => [s, t]
+    }
+}
+
+enum Transform {
+    Wrap { min: i32, max: i32, max_dif: i32 },
+    Octahedron(Option<Octahedron>)
+}
+
+impl Transform {
+    fn decode_data(&mut self, buffer: &mut DecoderBuffer) -> Result<(), io::Error> {
+        match self {
+            Transform::Wrap { min, max, max_dif } => {
+                *min = buffer.read_i32()?;
+                *max = buffer.read_i32()?;
+
+                let dif = *max as i64 - *min as i64;
+                if dif < 0 || dif >= i32::MAX as i64 {
+                    return Err(invalid("Invalid Draco wrap transform range."));
+                }
+
+                *max_dif = 1 + dif as i32;
+            },
+            Transform::Octahedron(octahedron) => {
+                let max_quantized_value = buffer.read_i32()?;
+                if max_quantized_value <= 0 || max_quantized_value % 2 == 0 {
+                    return Err(invalid("Invalid Draco normal transform."));
+                }
+
+                *octahedron = Some(Octahedron::new(32 - max_quantized_value.leading_zeros())?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply(&self, pred: &[i32], corr: &[i32], out: &mut [i32]) {
+        match self {
+            Transform::Wrap { min, max, max_dif } => {
+                for i in 0..out.len() {
+                    let value = pred[i].clamp(*min, *max).wrapping_add(corr[i]);
+                    out[i] = if value > *max {
+                        value.wrapping_sub(*max_dif)
+                    } else if value < *min {
+                        value.wrapping_add(*max_dif)
+                    } else {
+                        value
+                    };
+                }
+            },
+            Transform::Octahedron(octahedron) => {
+                let octahedron = octahedron.expect("transform data is decoded before values");
+                let orig = octahedron.original_value([pred[0], pred[1]], [corr[0], corr[1]]);
+                out[..2].copy_from_slice(&orig);
+            }
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum Method {
+    Difference,
+    Parallelogram,
+    ConstrainedMultiParallelogram,
+    TexCoordsPortable,
+    GeometricNormal
+}
+
+/// A prediction scheme and the transform applied to its corrections.
+pub struct PredictionScheme<'a> {
+    method:       Method,
+    transform:    Transform,
+    crease_edges: [Vec<bool>; MAX_NUM_PARALLELOGRAMS],
+    orientations: Vec<bool>,
+    flips:        Option<RAnsBitDecoder<'a>>
+}
+
+impl<'a> PredictionScheme<'a> {
+    /// Creates the scheme for the given method and transform ids, falling back to difference coding where the
+    /// reference decoder does.
+    pub fn new(method: i8, transform: i8, is_normal: bool, has_mesh: bool) -> Result<PredictionScheme<'a>, io::Error> {
+        let transform = match (transform, is_normal) {
+            (TRANSFORM_WRAP, false) => Transform::Wrap { min: 0, max: 0, max_dif: 1 },
+            (TRANSFORM_NORMAL_OCTAHEDRON_CANONICALIZED, true) => Transform::Octahedron(None),
+            _ => return Err(io::Error::new(io::ErrorKind::Unsupported, format!("Draco prediction transform {transform} is not supported.")))
+        };
+
+        let method = match method {
+            0 => Method::Difference,
+            1 => Method::Parallelogram,
+            4 => Method::ConstrainedMultiParallelogram,
+            5 => Method::TexCoordsPortable,
+            6 => Method::GeometricNormal,
+            2 | 3 => return Err(io::Error::new(io::ErrorKind::Unsupported, format!("Draco prediction method {method} is not supported."))),
+            _ => return Err(invalid(&format!("Unrecognized Draco prediction method {method}.")))
+        };
+
+        // Normals can only use the geometric normal prediction, and other attributes can't.
+        let method = match (&transform, method) {
+            _ if !has_mesh => Method::Difference,
+            (Transform::Wrap { .. }, Method::GeometricNormal) => Method::Difference,
+            (Transform::Octahedron(_), method) if method != Method::GeometricNormal => Method::Difference,
+            (_, method) => method
+        };
+
+        Ok(PredictionScheme {
+            method,
+            transform,
+            crease_edges: Default::default(),
+            orientations: Vec::new(),
+            flips: None
+        })
+    }
+
+    pub fn needs_positions(&self) -> bool {
+        matches!(self.method, Method::TexCoordsPortable | Method::GeometricNormal)
+    }
+
+    pub fn are_corrections_positive(&self) -> bool {
+        matches!(self.transform, Transform::Octahedron(_))
+    }
+
+    /// Decodes the data stored after the corrections, such as the transform range.
+    pub fn decode_data(&mut self, buffer: &mut DecoderBuffer<'a>, num_corners: usize) -> Result<(), io::Error> {
+        match self.method {
+            Method::ConstrainedMultiParallelogram => {
+                for crease_edges in &mut self.crease_edges {
+                    let num_flags = buffer.read_varint_u32()? as usize;
+                    if num_flags > num_corners {
+                        return Err(invalid("Too many Draco crease edges."));
+                    }
+
+                    if num_flags > 0 {
+                        let mut decoder = RAnsBitDecoder::start(buffer)?;
+                        *crease_edges = (0..num_flags).map(|_| decoder.decode_bit()).collect();
+                    }
+                }
+            },
+            Method::TexCoordsPortable => {
+                let num_orientations = buffer.read_i32()?;
+                if num_orientations < 0 || num_orientations as usize > num_corners {
+                    return Err(invalid("Invalid Draco texture coordinate orientations."));
+                }
+
+                let mut decoder = RAnsBitDecoder::start(buffer)?;
+                let mut last = true;
+                for _ in 0..num_orientations {
+                    if !decoder.decode_bit() {
+                        last = !last;
+                    }
+
+                    self.orientations.push(last);
+                }
+            },
+            _ => ()
+        }
+
+        self.transform.decode_data(buffer)?;
+
+        if self.method == Method::GeometricNormal {
+            self.flips = Some(RAnsBitDecoder::start(buffer)?);
+        }
+
+        Ok(())
+    }
+
+    /// Turns the corrections in `values` back into the original values, in place.
+    pub fn compute_original_values<T: Corners>(&mut self, values: &mut [i32], num_components: usize, mesh: Option<&MeshData<T>>,
+                                               point_ids: &[u32], positions: Option<&Positions>) -> Result<(), io::Error> {
+        if values.is_empty() || num_components == 0 {
+            return Ok(());
+        }
+
+        let num_entries = values.len() / num_components;
+        let mut pred = vec![0; num_components];
+        let mut corr = vec![0; num_components];
+
+        let mesh = match (&self.method, mesh) {
+            (Method::Difference, _) | (_, None) => {
+                for i in 0..num_entries {
+                    let offset = i * num_components;
+                    if i > 0 {
+                        pred.copy_from_slice(&values[offset - num_components..offset]);
+                    }
+
+                    corr.copy_from_slice(&values[offset..offset + num_components]);
+                    self.transform.apply(&pred, &corr, &mut values[offset..offset + num_components]);
+                }
+
+                return Ok(());
+            },
+            (_, Some(mesh)) => mesh
+        };
+
+        if mesh.value_to_corner.len() > num_entries {
+            return Err(invalid("Draco prediction data does not match the attribute."));
+        }
+
+        let error = || invalid("Corrupt Draco prediction data.");
+
+        match self.method {
+            Method::Parallelogram | Method::ConstrainedMultiParallelogram => {
+                corr.copy_from_slice(&values[..num_components]);
+                self.transform.apply(&pred, &corr, &mut values[..num_components]);
+
+                let mut multi_pred = vec![vec![0; num_components]; MAX_NUM_PARALLELOGRAMS];
+                let mut crease_positions = [0; MAX_NUM_PARALLELOGRAMS];
+
+                for p in 1..mesh.value_to_corner.len() {
+                    let start = mesh.value_to_corner[p];
+                    let offset = p * num_components;
+
+                    let num_used = if self.method == Method::Parallelogram {
+                        parallelogram(mesh, p, start, values, num_components, &mut pred) as usize
+                    } else {
+                        // Collect up to four parallelograms around the vertex, swinging left and then right.
+                        let mut num_parallelograms = 0;
+                        let mut corner = start;
+                        let mut first_pass = true;
+                        while corner != INVALID {
+                            if parallelogram(mesh, p, corner, values, num_components, &mut multi_pred[num_parallelograms]) {
+                                num_parallelograms += 1;
+                                if num_parallelograms == MAX_NUM_PARALLELOGRAMS {
+                                    break;
+                                }
+                            }
+
+                            corner = if first_pass { mesh.table.swing_left(corner) } else { mesh.table.swing_right(corner) };
+                            if corner == start {
+                                break;
+                            }
+
+                            if corner == INVALID && first_pass {
+                                first_pass = false;
+                                corner = mesh.table.swing_right(start);
+                            }
+                        }
+
+                        // The encoder flags the parallelograms that cross a crease, which are left out.
+                        let mut num_used = 0;
+                        pred.fill(0);
+                        for parallelogram in multi_pred.iter().take(num_parallelograms) {
+                            let context = num_parallelograms - 1;
+                            let is_crease = *self.crease_edges[context].get(crease_positions[context]).ok_or_else(error)?;
+                            crease_positions[context] += 1;
+
+                            if !is_crease {
+                                num_used += 1;
+                                for (sum, value) in pred.iter_mut().zip(parallelogram) {
+                                    *sum = sum.wrapping_add(*value);
+                                }
+                            }
+                        }
+
+                        for value in &mut pred {
+                            *value /= num_used.max(1);
+                        }
+
+                        num_used as usize
+                    };
+
+                    if num_used == 0 {
+                        pred.copy_from_slice(&values[offset - num_components..offset]);
+                    }
+
+                    corr.copy_from_slice(&values[offset..offset + num_components]);
+                    self.transform.apply(&pred, &corr, &mut values[offset..offset + num_components]);
+                }
+            },
+            Method::TexCoordsPortable => {
+                let positions = positions.ok_or_else(error)?;
+                if num_components != 2 {
+                    return Err(invalid("Draco texture coordinate prediction requires two components."));
+                }
+
+                for p in 0..mesh.value_to_corner.len() {
+                    let pred = self.predict_tex_coord(mesh, p, values, point_ids, positions).ok_or_else(error)?;
+                    let offset = p * 2;
+
+                    corr.copy_from_slice(&values[offset..offset + 2]);
+                    self.transform.apply(&pred, &corr, &mut values[offset..offset + 2]);
+                }
+            },
+            Method::GeometricNormal => {
+                let positions = positions.ok_or_else(error)?;
+                let octahedron = match &self.transform {
+                    Transform::Octahedron(Some(octahedron)) => *octahedron,
+                    _ => return Err(error())
+                };
+
+                if num_components != 2 {
+                    return Err(invalid("Draco normal prediction requires two components."));
+                }
+
+                for p in 0..mesh.value_to_corner.len() {
+                    let mut normal = predict_normal(mesh, mesh.value_to_corner[p], point_ids, positions).ok_or_else(error)?;
+                    octahedron.canonicalize_vector(&mut normal);
+
+                    if self.flips.as_mut().ok_or_else(error)?.decode_bit() {
+                        normal = normal.map(|c| -c);
+                    }
+
+                    let pred = octahedron.vector_to_coords(&normal);
+                    let offset = p * 2;
+
+                    corr.copy_from_slice(&values[offset..offset + 2]);
+                    self.transform.apply(&pred, &corr, &mut values[offset..offset + 2]);
+                }
+            },
+            Method::Difference => unreachable!()
+        }
+
+        Ok(())
+    }
+
+    fn predict_tex_coord<T: Corners>(&mut self, mesh: &MeshData<T>, data_id: usize, values: &[i32], point_ids: &[u32],
+                                     positions: &Positions) -> Option<[i32; 2]> {
+        let table = mesh.table;
+        let corner = mesh.value_to_corner[data_id];
+        let next_id = *mesh.vertex_to_value.get(table.vertex(table.next(corner)) as usize)?;
+        let prev_id = *mesh.vertex_to_value.get(table.vertex(table.previous(corner)) as usize)?;
+        let data_id_i = data_id as i32;
+
+        let uv = |id: i32| [values[id as usize * 2] as i64, values[id as usize * 2 + 1] as i64];
+        let position = |id: i32| positions.get(*point_ids.get(id as usize)?);
+
+        if prev_id < data_id_i && next_id < data_id_i {
+            let n_uv = uv(next_id);
+            let p_uv = uv(prev_id);
+            if p_uv == n_uv {
+                return Some([p_uv[0] as i32, p_uv[1] as i32]);
+            }
+
+            let tip_pos = position(data_id_i)?;
+            let next_pos = position(next_id)?;
+            let prev_pos = position(prev_id)?;
+
+            let pn = sub(prev_pos, next_pos);
+            let pn_norm2_squared = dot(pn, pn);
+            if pn_norm2_squared != 0 {
+                // Project the tip onto the opposite edge, then step away from it perpendicularly in UV space.
+                let cn = sub(next_pos, tip_pos);
+                let cn_dot_pn = dot(pn, cn);
+                let pn_uv = [p_uv[0].wrapping_sub(n_uv[0]), p_uv[1].wrapping_sub(n_uv[1])];
+
+                let n_uv_absmax = n_uv[0].abs().max(n_uv[1].abs());
+                if n_uv_absmax > i64::MAX / pn_norm2_squared {
+                    return None;
+                }
+
+                let pn_uv_absmax = pn_uv[0].abs().max(pn_uv[1].abs());
+                if pn_uv_absmax != 0 && cn_dot_pn > i64::MAX / pn_uv_absmax {
+                    return None;
+                }
+
+                let x_uv = [0, 1].map(|i| n_uv[i].wrapping_mul(pn_norm2_squared).wrapping_add(cn_dot_pn.wrapping_mul(pn_uv[i])));
+
+                let pn_absmax = pn[0].abs().max(pn[1].abs()).max(pn[2].abs());
+                if pn_absmax != 0 && cn_dot_pn > i64::MAX / pn_absmax {
+                    return None;
+                }
+
+                let x_pos = [0, 1, 2].map(|i| next_pos[i].wrapping_add(cn_dot_pn.wrapping_mul(pn[i]) / pn_norm2_squared));
+                let cx = sub(tip_pos, x_pos);
+                let cx_norm2_squared = dot(cx, cx) as u64;
+
+                let norm = int_sqrt(cx_norm2_squared.wrapping_mul(pn_norm2_squared as u64)) as i64;
+                let cx_uv = [pn_uv[1].wrapping_mul(norm), (-pn_uv[0]).wrapping_mul(norm)];
+
+                let orientation = self.orientations.pop()?;
+                let predicted = if orientation {
+                    [x_uv[0].wrapping_add(cx_uv[0]) / pn_norm2_squared, x_uv[1].wrapping_add(cx_uv[1]) / pn_norm2_squared]
+                } else {
+                    [x_uv[0].wrapping_sub(cx_uv[0]) / pn_norm2_squared, x_uv[1].wrapping_sub(cx_uv[1]) / pn_norm2_squared]
+                };
+
+                return Some([predicted[0] as i32, predicted[1] as i32]);
+            }
+        }
+
+        // Without a usable triangle, predict from a neighbour or the previous value.
+        let id = if next_id < data_id_i {
+            next_id
+        } else if data_id > 0 {
+            data_id_i - 1
+        } else {
+            return Some([0, 0]);
+        };
+
+        Some([values[id as usize * 2], values[id as usize * 2 + 1]])
+    }
+}
+
+/// Predicts the value at `corner` from the triangle opposite to it, if all of its values are already decoded.
+fn parallelogram<T: Corners>(mesh: &MeshData<T>, data_id: usize, corner: u32, values: &[i32], num_components: usize, out: &mut [i32]) -> bool {
+    let table = mesh.table;
+    let opposite = table.opposite(corner);
+    if opposite == INVALID {
+        return false;
+    }
+
+    let entry = |corner: u32| mesh.vertex_to_value.get(table.vertex(corner) as usize).copied().filter(|&e| e >= 0 && (e as usize) < data_id);
+    let (Some(opp), Some(next), Some(prev)) = (entry(opposite), entry(table.next(opposite)), entry(table.previous(opposite))) else {
+        return false;
+    };
+
+    let (opp, next, prev) = (opp as usize * num_components, next as usize * num_components, prev as usize * num_components);
+    for c in 0..num_components {
+        out[c] = (values[next + c] as i64 + values[prev + c] as i64 - values[opp + c] as i64) as i32;
+    }
+
+    true
+}
+
+/// Predicts a normal from the area weighted normals of the triangles around the corner's vertex.
+fn predict_normal<T: Corners>(mesh: &MeshData<T>, corner: u32, point_ids: &[u32], positions: &Positions) -> Option<[i64; 3]> {
+    let table = mesh.table;
+    let position = |corner: u32| {
+        let value = *mesh.vertex_to_value.get(table.vertex(corner) as usize)?;
+        positions.get(*point_ids.get(usize::try_from(value).ok()?)?)
+    };
+
+    let center = position(corner)?;
+    let mut normal = [0i64; 3];
+    for c in vertex_corners(table, corner) {
+        let next = sub(position(table.next(c))?, center);
+        let prev = sub(position(table.previous(c))?, center);
+        let cross = [
+            next[1].wrapping_mul(prev[2]).wrapping_sub(next[2].wrapping_mul(prev[1])),
+            next[2].wrapping_mul(prev[0]).wrapping_sub(next[0].wrapping_mul(prev[2])),
+            next[0].wrapping_mul(prev[1]).wrapping_sub(next[1].wrapping_mul(prev[0]))
+        ];
+
+        for i in 0..3 {
+            normal[i] = normal[i].wrapping_add(cross[i]);
+        }
+    }
+
+    const UPPER_BOUND: i64 = 1 << 29;
+    let abs_sum = normal.iter().fold(0i64, |sum, c| sum.wrapping_add(c.wrapping_abs()));
+    if abs_sum > UPPER_BOUND {
+        let quotient = abs_sum / UPPER_BOUND;
+        normal = normal.map(|c| c / quotient);
+    }
+
+    Some(normal.map(|c| c as i32 as i64))
+}
+
+fn sub(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+    [a[0].wrapping_sub(b[0]), a[1].wrapping_sub(b[1]), a[2].wrapping_sub(b[2])]
+}
+
+fn dot(a: [i64; 3], b: [i64; 3]) -> i64 {
+    a[0].wrapping_mul(b[0]).wrapping_add(a[1].wrapping_mul(b[1])).wrapping_add(a[2].wrapping_mul(b[2]))
+}
+
+fn int_sqrt(number: u64) -> u64 {
+    if number == 0 {
+        return 0;
+    }
+
+    let mut act_number = number;
+    let mut square_root = 1u64;
+    while act_number >= 2 {
+        square_root *= 2;
+        act_number /= 4;
+    }
+
+    loop {
+        square_root = (square_root + number / square_root) / 2;
+        if square_root.wrapping_mul(square_root) <= number {
+            return square_root;
+        }
+    }
+}
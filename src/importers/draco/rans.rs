@@ -0,0 +1,244 @@
+use std::io;
+
+use super::{buffer::DecoderBuffer, invalid};
+
+const IO_BASE: u32 = 256;
+const BIT_L_BASE: u32 = 4096;
+
+/// Reads the initial rANS state from the end of `data`, returning the state and the remaining offset.
+fn read_init(data: &[u8], l_base: u32, allow_four_bytes: bool) -> Result<(u32, usize), io::Error> {
+    let offset = data.len();
+    if offset < 1 {
+        return Err(invalid("Empty Draco rANS data."));
+    }
+
+    let le = |count: usize| data[offset - count..offset].iter().rev().fold(0u32, |acc, &b| (acc << 8) | b as u32);
+    let (state, offset) = match data[offset - 1] >> 6 {
+        0 => (data[offset - 1] as u32 & 0x3F, offset - 1),
+        1 if offset >= 2 => (le(2) & 0x3FFF, offset - 2),
+        2 if offset >= 3 => (le(3) & 0x3F_FFFF, offset - 3),
+        3 if allow_four_bytes && offset >= 4 => (le(4) & 0x3FFF_FFFF, offset - 4),
+        _ => return Err(invalid("Invalid Draco rANS header."))
+    };
+
+    let state = state + l_base;
+    if state as u64 >= l_base as u64 * IO_BASE as u64 {
+        return Err(invalid("Invalid Draco rANS state."));
+    }
+
+    Ok((state, offset))
+}
+
+/// Decodes single bits with a fixed probability of zero.
+pub struct RAnsBitDecoder<'a> {
+    data:      &'a [u8],
+    offset:    usize,
+    state:     u32,
+    prob_zero: u8
+}
+
+impl<'a> RAnsBitDecoder<'a> {
+    pub fn start(buffer: &mut DecoderBuffer<'a>) -> Result<RAnsBitDecoder<'a>, io::Error> {
+        let prob_zero = buffer.read_u8()?;
+        let size = buffer.read_varint()? as usize;
+        if size > buffer.remaining() {
+            return Err(invalid("Draco bit data is larger than the buffer."));
+        }
+
+        let data = buffer.read_bytes(size)?;
+        let (state, offset) = read_init(data, BIT_L_BASE, false)?;
+
+        Ok(RAnsBitDecoder {
+            data,
+            offset,
+            state,
+            prob_zero
+        })
+    }
+
+    pub fn decode_bit(&mut self) -> bool {
+        let p = 256 - self.prob_zero as u32;
+        if self.state < BIT_L_BASE && self.offset > 0 {
+            self.offset -= 1;
+            self.state = self.state * IO_BASE + self.data[self.offset] as u32;
+        }
+
+        let x = self.state;
+        let quot = x / 256;
+        let rem = x % 256;
+        let xn = quot * p;
+
+        if rem < p {
+            self.state = xn + rem;
+            true
+        } else {
+            self.state = x - xn - p;
+            false
+        }
+    }
+}
+
+struct Symbol {
+    prob:     u32,
+    cum_prob: u32
+}
+
+/// Decodes symbols coded with a probability table, as written by Draco's rANS symbol encoder.
+struct RAnsSymbolDecoder<'a> {
+    symbols:   Vec<Symbol>,
+    lookup:    Vec<u32>,
+    precision: u32,
+    data:      &'a [u8],
+    offset:    usize,
+    state:     u32
+}
+
+impl<'a> RAnsSymbolDecoder<'a> {
+    fn create(buffer: &mut DecoderBuffer<'a>, max_bit_length: u32) -> Result<RAnsSymbolDecoder<'a>, io::Error> {
+        let precision_bits = (3 * max_bit_length / 2).clamp(12, 20);
+        let precision = 1 << precision_bits;
+
+        let num_symbols = buffer.read_varint_u32()? as usize;
+        if num_symbols > precision as usize {
+            return Err(invalid("Too many Draco rANS symbols."));
+        }
+
+        let mut probabilities = vec![0u32; num_symbols];
+        let mut i = 0;
+        while i < num_symbols {
+            let prob_data = buffer.read_u8()?;
+            let token = prob_data & 3;
+
+            if token == 3 {
+                let offset = (prob_data >> 2) as usize;
+                if i + offset >= num_symbols {
+                    return Err(invalid("Invalid Draco rANS probability table."));
+                }
+
+                i += offset + 1;
+            } else {
+                let mut prob = (prob_data >> 2) as u32;
+                for b in 0..token as u32 {
+                    prob |= (buffer.read_u8()? as u32) << (8 * (b + 1) - 2);
+                }
+
+                probabilities[i] = prob;
+                i += 1;
+            }
+        }
+
+        let mut symbols = Vec::with_capacity(num_symbols);
+        let mut lookup = vec![0; precision as usize];
+        let mut cum_prob = 0u32;
+        for (i, &prob) in probabilities.iter().enumerate() {
+            let end = cum_prob.checked_add(prob).filter(|&end| end <= precision).ok_or_else(|| invalid("Invalid Draco rANS probability table."))?;
+            lookup[cum_prob as usize..end as usize].fill(i as u32);
+            symbols.push(Symbol { prob, cum_prob });
+            cum_prob = end;
+        }
+
+        if num_symbols > 0 && cum_prob != precision {
+            return Err(invalid("Invalid Draco rANS probability table."));
+        }
+
+        Ok(RAnsSymbolDecoder {
+            symbols,
+            lookup,
+            precision,
+            data: &[],
+            offset: 0,
+            state: 0
+        })
+    }
+
+    fn start(&mut self, buffer: &mut DecoderBuffer<'a>) -> Result<(), io::Error> {
+        let size = buffer.read_varint()?;
+        if size > buffer.remaining() as u64 {
+            return Err(invalid("Draco symbol data is larger than the buffer."));
+        }
+
+        self.data = buffer.read_bytes(size as usize)?;
+        (self.state, self.offset) = read_init(self.data, 4 * self.precision, true)?;
+        Ok(())
+    }
+
+    fn decode_symbol(&mut self) -> u32 {
+        let l_base = 4 * self.precision;
+        while self.state < l_base && self.offset > 0 {
+            self.offset -= 1;
+            self.state = self.state * IO_BASE + self.data[self.offset] as u32;
+        }
+
+        let quot = self.state / self.precision;
+        let rem = self.state % self.precision;
+        let value = self.lookup[rem as usize];
+        let symbol = &self.symbols[value as usize];
+        self.state = quot * symbol.prob + rem - symbol.cum_prob;
+
+        value
+    }
+}
+
+/// Decodes `num_values` entropy coded symbols, using either the tagged or the raw scheme.
+pub fn decode_symbols(buffer: &mut DecoderBuffer, num_values: usize, num_components: usize) -> Result<Vec<u32>, io::Error> {
+    if num_values == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut values = Vec::with_capacity(num_values);
+    match buffer.read_u8()? {
+        // Tagged: each value is stored in the bit buffer, with its bit length coded as a symbol.
+        0 => {
+            let mut tags = RAnsSymbolDecoder::create(buffer, 5)?;
+            if tags.symbols.is_empty() {
+                return Err(invalid("Draco symbol table is empty."));
+            }
+
+            tags.start(buffer)?;
+            buffer.start_bit_decoding(false)?;
+
+            while values.len() < num_values {
+                let bit_length = tags.decode_symbol();
+                if bit_length > 32 {
+                    return Err(invalid("Invalid Draco symbol bit length."));
+                }
+
+                for _ in 0..num_components.max(1) {
+                    values.push(buffer.read_bits(bit_length)?);
+                }
+            }
+
+            buffer.end_bit_decoding();
+            values.truncate(num_values);
+        },
+        // Raw: every value is a symbol.
+        1 => {
+            let max_bit_length = buffer.read_u8()? as u32;
+            if !(1..=18).contains(&max_bit_length) {
+                return Err(invalid("Invalid Draco symbol bit length."));
+            }
+
+            let mut decoder = RAnsSymbolDecoder::create(buffer, max_bit_length)?;
+            if decoder.symbols.is_empty() {
+                return Err(invalid("Draco symbol table is empty."));
+            }
+
+            decoder.start(buffer)?;
+            for _ in 0..num_values {
+                values.push(decoder.decode_symbol());
+            }
+        },
+        scheme => return Err(invalid(&format!("Unrecognized Draco symbol scheme {scheme}.")))
+    }
+
+    Ok(values)
+}
+
+/// Maps the zig-zag coded symbols back to signed integers.
+pub fn symbol_to_signed(value: u32) -> i32 {
+    if value & 1 == 0 {
+        (value >> 1) as i32
+    } else {
+        -((value >> 1) as i32) - 1
+    }
+}
@@ -0,0 +1,80 @@
+use std::{fmt, io};
+
+/// An error from importing a file.
+///
+/// Errors caused by the document carry the JSON path of the offending element, such as
+/// `meshes[0].primitives[1].indices`.
+#[derive(Debug)]
+pub enum ImportError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// A file referenced by the document could not be loaded. The path is that of the element holding
+    /// the URI, such as `buffers[0].uri`.
+    Resource { path: String, error: io::Error },
+    /// The document is not valid JSON.
+    Json(serde_json::Error),
+    /// A required property is missing.
+    MissingField { path: String },
+    /// A property has the wrong JSON type, or a value out of range.
+    InvalidType { path: String, expected: &'static str },
+    /// An index refers to an element that does not exist.
+    InvalidIndex { path: String, index: i64 },
+    /// The data is malformed, such as an unrecognized enum value or corrupt compressed data.
+    InvalidData { path: String, message: String },
+    /// The data is valid, but uses something impasse does not support.
+    Unsupported { path: String, message: String }
+}
+
+impl ImportError {
+    /// The JSON path of the element that caused the error, if it was caused by one.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            ImportError::Io(_) | ImportError::Json(_) => None,
+            ImportError::MissingField { path }
+            | ImportError::InvalidType { path, .. }
+            | ImportError::InvalidIndex { path, .. }
+            | ImportError::InvalidData { path, .. }
+            | ImportError::Unsupported { path, .. }
+            | ImportError::Resource { path, .. } => Some(path)
+        }
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(error) => write!(f, "{error}"),
+            ImportError::Json(error) => write!(f, "Invalid JSON: {error}"),
+            ImportError::MissingField { path } => write!(f, "{path}: Required property is missing."),
+            ImportError::InvalidType { path, expected } => write!(f, "{path}: Expected {expected}."),
+            ImportError::InvalidIndex { path, index } => write!(f, "{path}: Index {index} does not exist."),
+            // Errors about the file as a whole, such as a malformed GLB header, have no path.
+            ImportError::InvalidData { path, message } | ImportError::Unsupported { path, message } if path.is_empty() => write!(f, "{message}"),
+            ImportError::InvalidData { path, message } => write!(f, "{path}: {message}"),
+            ImportError::Unsupported { path, message } => write!(f, "{path}: {message}"),
+            ImportError::Resource { path, error } => write!(f, "{path}: {error}")
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Io(error) | ImportError::Resource { error, .. } => Some(error),
+            ImportError::Json(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(error: io::Error) -> Self {
+        ImportError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(error: serde_json::Error) -> Self {
+        ImportError::Json(error)
+    }
+}
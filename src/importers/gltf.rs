@@ -1,11 +1,9 @@
-use std::{io, collections::HashMap, path::Path};
+use std::{io::{self, Read, Seek, SeekFrom}, collections::HashMap, path::Path};
 
 use base64::Engine;
 use serde_json::Value;
 
-use crate::binary_reader::BinaryReader;
-
-use super::Importer;
+use super::{draco::AttributeValues, meshopt, percent_decode, FileResolver, ImportError, Importer, ResourceResolver};
 
 #[derive(Debug)]
 pub struct Asset {
@@ -33,14 +31,127 @@ pub struct Node {
     pub scale:       crate::Vec3,
     pub translation: crate::Vec3,
     pub weights:     Option<Vec<f32>>,
+    pub name:        Option<String>,
+    /// The `KHR_lights_punctual` light attached to this node, if any.
+    pub light:       Option<i32>
+}
+
+#[derive(Debug)]
+pub struct CameraPerspective {
+    pub aspect_ratio: Option<f32>,
+    pub yfov:         f32,
+    pub zfar:         Option<f32>,
+    pub znear:        f32
+}
+
+#[derive(Debug)]
+pub struct CameraOrthographic {
+    pub xmag:  f32,
+    pub ymag:  f32,
+    pub zfar:  f32,
+    pub znear: f32
+}
+
+#[derive(Debug)]
+pub enum CameraType {
+    Perspective(CameraPerspective),
+    Orthographic(CameraOrthographic)
+}
+
+#[derive(Debug)]
+pub struct Camera {
+    pub camera_type: CameraType,
     pub name:        Option<String>
 }
 
+#[derive(Debug)]
+pub struct LightSpot {
+    pub inner_cone_angle: f32,
+    pub outer_cone_angle: f32
+}
+
+#[derive(Debug)]
+pub enum LightType {
+    Directional,
+    Point,
+    Spot(LightSpot)
+}
+
+/// A `KHR_lights_punctual` light.
+#[derive(Debug)]
+pub struct Light {
+    pub color:      crate::Vec3,
+    pub intensity:  f32,
+    pub light_type: LightType,
+    pub range:      Option<f32>,
+    pub name:       Option<String>
+}
+
+#[derive(Debug)]
+pub struct Skin {
+    pub inverse_bind_matrices: Option<i32>,
+    pub skeleton:              Option<i32>,
+    pub joints:                Vec<i32>,
+    pub name:                  Option<String>
+}
+
+#[derive(Debug)]
+pub enum AnimationPath {
+    Translation,
+    Rotation,
+    Scale,
+    Weights
+}
+
+#[derive(Debug)]
+pub struct AnimationChannelTarget {
+    pub node: Option<i32>,
+    pub path: AnimationPath
+}
+
+#[derive(Debug)]
+pub struct AnimationChannel {
+    pub sampler: i32,
+    pub target:  AnimationChannelTarget
+}
+
+#[derive(Debug)]
+pub enum Interpolation {
+    Linear,
+    Step,
+    CubicSpline
+}
+
+#[derive(Debug)]
+pub struct AnimationSampler {
+    pub input:         i32,
+    pub interpolation: Interpolation,
+    pub output:        i32
+}
+
+#[derive(Debug)]
+pub struct Animation {
+    pub channels: Vec<AnimationChannel>,
+    pub samplers: Vec<AnimationSampler>,
+    pub name:     Option<String>
+}
+
+/// A `KHR_texture_transform` UV transform.
+#[derive(Debug)]
+pub struct TextureTransform {
+    pub offset:    crate::Vec2,
+    pub rotation:  f32,
+    pub scale:     crate::Vec2,
+    /// Overrides the texture info's `tex_coord`, if present.
+    pub tex_coord: Option<i32>
+}
+
 #[derive(Debug)]
 pub struct TextureInfo {
     pub index:     i32,
     pub tex_coord: i32,
-    pub scalar:    f32
+    pub scalar:    f32,
+    pub transform: Option<TextureTransform>
 }
 
 #[derive(Debug)]
@@ -89,14 +200,25 @@ pub struct MeshPrimitive {
     pub indices:    Option<i32>,
     pub material:   Option<i32>,
     pub mode:       Topology,
-    // TODO: Targets, I have no idea what the JSON object looks like so unable to implement
+    pub targets:    Option<Vec<HashMap<String, i32>>>,
+    /// The compressed data from `KHR_draco_mesh_compression`. Once imported, the accessors already point at the decoded data.
+    pub draco:      Option<DracoCompression>
+}
+
+#[derive(Debug)]
+pub struct DracoCompression {
+    pub buffer_view: i32,
+    /// Maps attribute semantics to the Draco unique ids.
+    pub attributes:  HashMap<String, i32>
 }
 
 #[derive(Debug)]
 pub struct Mesh {
-    pub primitives: Vec<MeshPrimitive>,
-    pub weights:    Option<Vec<f32>>,
-    pub name:       Option<String>
+    pub primitives:   Vec<MeshPrimitive>,
+    pub weights:      Option<Vec<f32>>,
+    pub name:         Option<String>,
+    /// The morph target names, from the de facto `extras.targetNames` convention.
+    pub target_names: Option<Vec<String>>
 }
 
 #[derive(Debug)]
@@ -114,6 +236,14 @@ pub struct Image {
     pub name:        Option<String>
 }
 
+/// The encoded bytes of an image stored in the document itself.
+#[derive(Debug)]
+pub struct EmbeddedImage {
+    pub data:      Vec<u8>,
+    /// The MIME type from the image, or from its data URI, if either has one.
+    pub mime_type: Option<String>
+}
+
 #[derive(Debug)]
 pub enum ComponentType {
     Byte,
@@ -124,6 +254,78 @@ pub enum ComponentType {
     Float
 }
 
+impl ComponentType {
+    /// The size of a single component, in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            ComponentType::Byte | ComponentType::UnsignedByte => 1,
+            ComponentType::Short | ComponentType::UnsignedShort => 2,
+            ComponentType::UnsignedInt | ComponentType::Float => 4
+        }
+    }
+}
+
+/// A type accessor components can be read as.
+pub trait Component: Copy {
+    /// Whether components of this type can be converted without losing their meaning.
+    fn accepts(component_type: &ComponentType) -> bool;
+
+    /// Converts a single little endian component.
+    fn read(component_type: &ComponentType, normalized: bool, bytes: &[u8]) -> Self;
+}
+
+impl Component for f32 {
+    fn accepts(_: &ComponentType) -> bool {
+        true
+    }
+
+    /// Integer components, such as the ones used by `KHR_mesh_quantization`, are normalized if the accessor says so.
+    fn read(component_type: &ComponentType, normalized: bool, bytes: &[u8]) -> Self {
+        match component_type {
+            ComponentType::Byte => {
+                let value = bytes[0] as i8 as f32;
+                if normalized { (value / 127.0).max(-1.0) } else { value }
+            },
+
+            ComponentType::UnsignedByte => {
+                let value = bytes[0] as f32;
+                if normalized { value / 255.0 } else { value }
+            },
+
+            ComponentType::Short => {
+                let value = i16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+                if normalized { (value / 32767.0).max(-1.0) } else { value }
+            },
+
+            ComponentType::UnsignedShort => {
+                let value = u16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+                if normalized { value / 65535.0 } else { value }
+            },
+
+            ComponentType::UnsignedInt => {
+                let value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32;
+                if normalized { value / 4294967295.0 } else { value }
+            },
+
+            ComponentType::Float => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        }
+    }
+}
+
+impl Component for u32 {
+    fn accepts(component_type: &ComponentType) -> bool {
+        matches!(component_type, ComponentType::UnsignedByte | ComponentType::UnsignedShort | ComponentType::UnsignedInt)
+    }
+
+    fn read(component_type: &ComponentType, _: bool, bytes: &[u8]) -> Self {
+        match component_type {
+            ComponentType::UnsignedByte => bytes[0] as u32,
+            ComponentType::UnsignedShort => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+            _ => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum AccessorType {
     Scalar,
@@ -135,6 +337,31 @@ pub enum AccessorType {
     Mat4
 }
 
+impl AccessorType {
+    /// The number of components in a single element.
+    pub fn num_components(&self) -> usize {
+        match self {
+            AccessorType::Scalar => 1,
+            AccessorType::Vec2 => 2,
+            AccessorType::Vec3 => 3,
+            AccessorType::Vec4 => 4,
+            AccessorType::Mat2 => 4,
+            AccessorType::Mat3 => 9,
+            AccessorType::Mat4 => 16
+        }
+    }
+
+    /// The number of components in a single column. Vectors and scalars have one column.
+    pub fn num_rows(&self) -> usize {
+        match self {
+            AccessorType::Mat2 => 2,
+            AccessorType::Mat3 => 3,
+            AccessorType::Mat4 => 4,
+            _ => self.num_components()
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AccessorSparseIndices {
     pub buffer_view:    i32,
@@ -182,7 +409,35 @@ pub struct BufferView {
     pub byte_length: i32,
     pub byte_stride: Option<i32>,
     pub target:      Option<Target>,
-    pub name:        Option<String>
+    pub name:        Option<String>,
+    /// The compressed data from `EXT_meshopt_compression`. Once imported, the view already points at the decoded data.
+    pub meshopt:     Option<MeshoptCompression>
+}
+
+#[derive(Debug)]
+pub enum MeshoptMode {
+    Attributes,
+    Triangles,
+    Indices
+}
+
+#[derive(Debug)]
+pub enum MeshoptFilter {
+    None,
+    Octahedral,
+    Quaternion,
+    Exponential
+}
+
+#[derive(Debug)]
+pub struct MeshoptCompression {
+    pub buffer:      i32,
+    pub byte_offset: i32,
+    pub byte_length: i32,
+    pub byte_stride: i32,
+    pub count:       i32,
+    pub mode:        MeshoptMode,
+    pub filter:      MeshoptFilter
 }
 
 #[derive(Debug)]
@@ -236,179 +491,262 @@ pub struct Gltf {
     pub accessors:    Option<Vec<Accessor>>,
     pub buffer_views: Option<Vec<BufferView>>,
     pub samplers:     Option<Vec<Sampler>>,
+    pub skins:        Option<Vec<Skin>>,
+    pub animations:   Option<Vec<Animation>>,
+    pub cameras:      Option<Vec<Camera>>,
+    pub lights:       Option<Vec<Light>>,
 
     pub buffers:      Option<Vec<Buffer>>
 }
 
-impl Importer for Gltf {
-    fn import(path: &str) -> Result<Self, io::Error> {
-        let data = std::fs::read(path)?;
+impl Gltf {
+    /// Reads every element of an accessor, converting each component to `T`. `N` must match the
+    /// number of components of the accessor's type, such as 3 for `VEC3`.
+    ///
+    /// Elements are read with the accessor's byte offset and the buffer view's byte stride, so
+    /// interleaved buffers are supported. Sparse values are applied on top of the buffer view's
+    /// data, or on top of zeros if the accessor has no buffer view.
+    pub fn read_accessor<T: Component + Default, const N: usize>(&self, index: i32) -> Result<Vec<[T; N]>, ImportError> {
+        let path = format!("accessors[{index}]");
+        let accessor = self.accessors.as_deref().and_then(|accessors| accessors.get(usize::try_from(index).ok()?))
+            .ok_or_else(|| ImportError::InvalidIndex { path: path.clone(), index: index as i64 })?;
+
+        let num_components = accessor.accessor_type.num_components();
+        if num_components != N {
+            return Err(ImportError::InvalidData { path: format!("{path}.type"), message: format!("Expected {N} components, found {num_components}.") });
+        }
+
+        if !T::accepts(&accessor.component_type) {
+            return Err(ImportError::InvalidData { path: format!("{path}.componentType"), message: format!("Components of type {:?} can not be read here.", accessor.component_type) });
+        }
+
+        let count = accessor.count as usize;
+        let rows = accessor.accessor_type.num_rows();
+
+        let mut elements = match accessor.buffer_view {
+            Some(view_index) => {
+                let (data, stride) = self.buffer_view_data(view_index)?;
+                read_elements(data, accessor.byte_offset as usize, stride, count, rows, &accessor.component_type, accessor.normalized)
+                    .ok_or_else(|| ImportError::InvalidData { path: path.clone(), message: "Accessor is out of range of its buffer view.".to_string() })?
+            },
+
+            // An accessor without a buffer view is all zeros, unless sparse values replace them. Nothing in the file
+            // backs the count, so it is checked before allocating.
+            None => {
+                let mut zeros = Vec::new();
+                zeros.try_reserve_exact(count)
+                    .map_err(|_| ImportError::InvalidData { path: format!("{path}.count"), message: format!("Not enough memory for {count} elements.") })?;
+                zeros.resize(count, [T::default(); N]);
+                zeros
+            }
+        };
 
-        let mut reader = BinaryReader::new(&data);
-        // It's a GLB!
-        let (json, is_glb) = if reader.read_u32() == 0x46546C67 {
-            reader.read_u32(); // version
-            reader.read_u32(); // length
+        if let Some(sparse) = &accessor.sparse {
+            let sparse_count = sparse.count as usize;
 
-            let json_length = reader.read_u32();
+            let (data, _) = self.buffer_view_data(sparse.indices.buffer_view)?;
+            let indices = read_elements::<u32, 1>(data, sparse.indices.byte_offset as usize, None, sparse_count, 1, &sparse.indices.component_type, false)
+                .ok_or_else(|| ImportError::InvalidData { path: format!("{path}.sparse.indices"), message: "Sparse indices are out of range of their buffer view.".to_string() })?;
 
-            if reader.read_u32() != 0x4E4F534A {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, "Expected JSON, did not find JSON."));
+            let (data, _) = self.buffer_view_data(sparse.values.buffer_view)?;
+            let values = read_elements::<T, N>(data, sparse.values.byte_offset as usize, None, sparse_count, rows, &accessor.component_type, accessor.normalized)
+                .ok_or_else(|| ImportError::InvalidData { path: format!("{path}.sparse.values"), message: "Sparse values are out of range of their buffer view.".to_string() })?;
+
+            for ([index], value) in indices.into_iter().zip(values) {
+                let element = elements.get_mut(index as usize)
+                    .ok_or_else(|| ImportError::InvalidData { path: format!("{path}.sparse.indices"), message: format!("Sparse index {index} is out of range of the accessor.") })?;
+                *element = value;
             }
+        }
+
+        Ok(elements)
+    }
+
+    /// Reads the encoded bytes of an embedded image, from its buffer view or its data URI. Returns
+    /// `None` if the image is an external file.
+    pub fn read_image(&self, index: i32) -> Result<Option<EmbeddedImage>, ImportError> {
+        let path = format!("images[{index}]");
+        let image = self.images.as_deref().and_then(|images| images.get(usize::try_from(index).ok()?))
+            .ok_or_else(|| ImportError::InvalidIndex { path: path.clone(), index: index as i64 })?;
+
+        match (&image.uri, image.buffer_view) {
+            (Some(uri), _) => {
+                let Some(data) = uri.strip_prefix("data:") else {
+                    return Ok(None);
+                };
+
+                let (media_type, data) = decode_data_uri(data).map_err(|message| ImportError::InvalidData { path: format!("{path}.uri"), message })?;
+                let mime_type = image.mime_type.clone().or_else(|| Some(media_type.to_string()).filter(|mt| !mt.is_empty()));
+
+                Ok(Some(EmbeddedImage { data, mime_type }))
+            },
+
+            (None, Some(view)) => Ok(Some(EmbeddedImage { data: self.buffer_view_data(view)?.0.to_vec(), mime_type: image.mime_type.clone() })),
+
+            (None, None) => Err(ImportError::MissingField { path: format!("{path}.uri") })
+        }
+    }
+
+    /// Gets the bytes a buffer view covers, along with its byte stride.
+    fn buffer_view_data(&self, index: i32) -> Result<(&[u8], Option<usize>), ImportError> {
+        // The indices were checked when they were imported.
+        let view = &self.buffer_views.as_deref().unwrap_or_default()[index as usize];
+        let data = buffer_range(self.buffers.as_deref().unwrap_or_default(), view.buffer, view.byte_offset, view.byte_length)
+            .ok_or_else(|| ImportError::InvalidData { path: format!("bufferViews[{index}]"), message: "Buffer view is out of range of its buffer.".to_string() })?;
+
+        Ok((data, view.byte_stride.map(|bs| bs as usize)))
+    }
+}
+
+/// Reads `count` elements of `N` components in columns of `rows`, starting at `offset`. Elements are tightly packed
+/// if there is no stride. Returns `None` if the elements are out of range of `data`.
+fn read_elements<T: Component, const N: usize>(data: &[u8], offset: usize, stride: Option<usize>, count: usize, rows: usize,
+                                               component_type: &ComponentType, normalized: bool) -> Option<Vec<[T; N]>> {
+    let component_size = component_type.size();
+    // Each column of a matrix starts on a 4 byte boundary, so byte and short MAT2 and MAT3 columns are padded.
+    let column_size = if rows == N { rows * component_size } else { (rows * component_size).next_multiple_of(4) };
+    let element_size = N / rows * column_size;
+    // Quantized attributes are padded to 4 bytes, so the stride is often larger than the element.
+    let stride = stride.unwrap_or(element_size);
+
+    if count > 0 && offset + (count - 1) * stride + element_size > data.len() {
+        return None;
+    }
+
+    Some((0..count).map(|element| {
+        let bytes = &data[offset + element * stride..];
+        std::array::from_fn(|component| {
+            T::read(component_type, normalized, &bytes[component / rows * column_size + component % rows * component_size..])
+        })
+    }).collect())
+}
+
+impl Importer for Gltf {
+    fn import(path: &str) -> Result<Self, ImportError> {
+        Gltf::import_with(path, &mut FileResolver)
+    }
+}
+
+impl Gltf {
+    /// Imports a glTF or GLB file, loading it and its external buffers with `resolver`. The file is
+    /// resolved with an empty base, and its buffers relative to the file's directory.
+    pub fn import_with(path: &str, resolver: &mut impl ResourceResolver) -> Result<Self, ImportError> {
+        let data = resolver.resolve(Path::new(""), path)?;
+        let base = Path::new(path).parent().unwrap_or(Path::new(""));
+
+        Gltf::parse(&data, base, resolver)
+    }
+
+    /// Imports a glTF or GLB file from the rest of `reader`.
+    pub fn from_reader<R: Read + Seek>(mut reader: R, resolver: &mut impl ResourceResolver) -> Result<Self, ImportError> {
+        let start = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let mut data = Vec::with_capacity(end.saturating_sub(start) as usize);
+        reader.read_to_end(&mut data)?;
+
+        Gltf::from_bytes(&data, resolver)
+    }
+
+    /// Imports a glTF or GLB file from memory. There is no directory to load external buffers from, so
+    /// `resolver` is given an empty base, and usually a closure that is called with each buffer's URI.
+    pub fn from_bytes(data: &[u8], resolver: &mut impl ResourceResolver) -> Result<Self, ImportError> {
+        Gltf::parse(data, Path::new(""), resolver)
+    }
 
-            (serde_json::from_slice::<Value>(reader.read_bytes(json_length as usize))?, true)
+    fn parse(data: &[u8], base: &Path, resolver: &mut impl ResourceResolver) -> Result<Self, ImportError> {
+        // It's a GLB!
+        let (json, mut bin) = if data.starts_with(b"glTF") {
+            let (json, bin) = read_glb(data)?;
+            (serde_json::from_slice::<Value>(json)?, bin)
         } else {
-            (serde_json::from_slice(&data)?, false)
+            (serde_json::from_slice::<Value>(data)?, None)
         };
-
-        // Get the asset information - no need to check here, a GLTF file is required to have "asset".
-        let s_asset = &json["asset"];
+        let is_glb = bin.is_some();
+
+        let json = Json::new(&json);
+
+        // Indices are checked against the number of elements they point at as they are read.
+        let len = |value: Option<Json>| value.map_or(0, |v| v.value.as_array().map_or(0, Vec::len));
+        let num_scenes = len(json.get("scenes"));
+        let num_nodes = len(json.get("nodes"));
+        let num_materials = len(json.get("materials"));
+        let num_meshes = len(json.get("meshes"));
+        let num_textures = len(json.get("textures"));
+        let num_images = len(json.get("images"));
+        let num_accessors = len(json.get("accessors"));
+        let num_buffer_views = len(json.get("bufferViews"));
+        let num_samplers = len(json.get("samplers"));
+        let num_skins = len(json.get("skins"));
+        let num_cameras = len(json.get("cameras"));
+        let num_buffers = len(json.get("buffers"));
+        let num_lights = len(json.get("extensions").and_then(|ex| ex.get("KHR_lights_punctual")).and_then(|kl| kl.get("lights")));
+
+        // A glTF file is required to have "asset".
+        let s_asset = json.field("asset")?;
         let asset = Asset {
-            version: s_asset["version"].as_str().unwrap().to_string(),
-            copyright: if let Some(cr) = s_asset.get("copyright") { Some(cr.as_str().unwrap().to_string()) } else { None },
-            generator: if let Some(gn) = s_asset.get("generator") { Some(gn.as_str().unwrap().to_string()) } else { None },
-            min_version: if let Some(mv) = s_asset.get("minVersion") { Some(mv.as_str().unwrap().to_string()) } else { None },
+            version: s_asset.field("version")?.string()?,
+            copyright: s_asset.get_string("copyright")?,
+            generator: s_asset.get_string("generator")?,
+            min_version: s_asset.get_string("minVersion")?
         };
 
         // Get the default scene, if any.
-        let scene = if let Some(sc) = json.get("scene") {
-            Some(sc.as_i64().unwrap() as i32)
-        } else {
-            None
-        };
+        let scene = json.get_index("scene", num_scenes)?;
 
         // Get the scenes information.
-        let scenes = if let Some(s_scenes) = json.get("scenes") {
-            let mut tmp_scenes = Vec::new();
-            for scene in s_scenes.as_array().unwrap().into_iter() {
-                let name = if let Some(nm) = scene.get("name") {
-                    Some(nm.as_str().unwrap().to_string())
-                } else {
-                    None
-                };
-                
-                let nodes = if let Some(s_nodes) = scene.get("nodes") {
-                    let s_nodes = s_nodes.as_array().unwrap();
-
-                    let mut nodes = Vec::with_capacity(s_nodes.len());
-                    for node in s_nodes {
-                        nodes.push(node.as_i64().unwrap() as i32);
-                    }
+        let scenes = json.get("scenes").map(|s_scenes| -> Result<_, ImportError> {
+            let mut scenes = Vec::new();
+            for scene in s_scenes.array()? {
+                let name = scene.get_string("name")?;
 
-                    Some(nodes)
-                } else {
-                    None
-                };
+                let nodes = scene.get("nodes").map(|nd| nd.indices(num_nodes)).transpose()?;
 
-                tmp_scenes.push(Scene { 
+                scenes.push(Scene {
                     name,
                     nodes
                 });
             }
 
-            Some(tmp_scenes)
-        } else {
-            None
-        };
+            Ok(scenes)
+        }).transpose()?;
 
-    
-        let nodes = if let Some(s_nodes) = json.get("nodes") {
-            let s_nodes = s_nodes.as_array().unwrap();
+        let nodes = json.get("nodes").map(|s_nodes| -> Result<_, ImportError> {
+            let s_nodes = s_nodes.array()?;
 
             let mut nodes = Vec::with_capacity(s_nodes.len());
             for value in s_nodes {
-                let camera = if let Some(cm) = value.get("camera") {
-                    Some(cm.as_i64().unwrap() as i32 )
-                } else {
-                    None
-                };
-                
-                let children = if let Some(ch) = value.get("children") {
-                    let ch = ch.as_array().unwrap();
-
-                    let mut children = Vec::with_capacity(ch.len());
-                    for child in ch {
-                        children.push(child.as_i64().unwrap() as i32);
-                    }
+                let camera = value.get_index("camera", num_cameras)?;
 
-                    Some(children)
-                } else {
-                    None
-                };
+                let children = value.get("children").map(|ch| ch.indices(num_nodes)).transpose()?;
 
-                let skin = if let Some(sk) = value.get("skin") {
-                    Some(sk.as_i64().unwrap() as i32)
-                } else {
-                    None
-                };
+                let skin = value.get_index("skin", num_skins)?;
 
                 let matrix = if let Some(mat) = value.get("matrix") {
-                    let mat = mat.as_array().unwrap();
-
-                    crate::Mat4 {
-                        m11: mat[0].as_f64().unwrap() as f32, m12: mat[1].as_f64().unwrap() as f32, m13: mat[2].as_f64().unwrap() as f32, m14: mat[3].as_f64().unwrap() as f32,
-                        m21: mat[4].as_f64().unwrap() as f32, m22: mat[5].as_f64().unwrap() as f32, m23: mat[6].as_f64().unwrap() as f32, m24: mat[7].as_f64().unwrap() as f32,
-                        m31: mat[8].as_f64().unwrap() as f32, m32: mat[9].as_f64().unwrap() as f32, m33: mat[10].as_f64().unwrap() as f32, m34: mat[11].as_f64().unwrap() as f32,
-                        m41: mat[12].as_f64().unwrap() as f32, m42: mat[13].as_f64().unwrap() as f32, m43: mat[14].as_f64().unwrap() as f32, m44: mat[15].as_f64().unwrap() as f32 
-                    }
-                } else {
-                    // Return an identity matrix.
-                    crate::Mat4 {
-                        m11: 1.0, m12: 0.0, m13: 0.0, m14: 0.0,
-                        m21: 0.0, m22: 1.0, m23: 0.0, m24: 0.0,
-                        m31: 0.0, m32: 0.0, m33: 1.0, m34: 0.0,
-                        m41: 0.0, m42: 0.0, m43: 0.0, m44: 1.0
-                    }
-                };
-
-                let mesh = if let Some(msh) = value.get("mesh") {
-                    Some(msh.as_i64().unwrap() as i32)
-                } else {
-                    None
-                };
-
-                let rotation = if let Some(rot) = value.get("rotation") {
-                    let rot = rot.as_array().unwrap();
-
-                    crate::Vec4 { x: rot[0].as_f64().unwrap() as f32, y: rot[1].as_f64().unwrap() as f32, z: rot[2].as_f64().unwrap() as f32, w: rot[3].as_f64().unwrap() as f32 }
+                    let [m11, m12, m13, m14, m21, m22, m23, m24, m31, m32, m33, m34, m41, m42, m43, m44] = mat.floats()?;
+                    crate::Mat4 { m11, m12, m13, m14, m21, m22, m23, m24, m31, m32, m33, m34, m41, m42, m43, m44 }
                 } else {
-                    crate::Vec4 { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }
+                    crate::Mat4::IDENTITY
                 };
 
-                let scale = if let Some(sc) = value.get("scale") {
-                    let sc = sc.as_array().unwrap();
-
-                    crate::Vec3 { x: sc[0].as_f64().unwrap() as f32, y: sc[1].as_f64().unwrap() as f32, z: sc[2].as_f64().unwrap() as f32 }
-                } else {
-                    crate::Vec3 { x: 1.0, y: 1.0, z: 1.0 }
-                };
+                let mesh = value.get_index("mesh", num_meshes)?;
 
-                let translation = if let Some(tr) = value.get("translation") {
-                    let tr = tr.as_array().unwrap();
+                let rotation = value.get("rotation").map(|rot| rot.vec4()).transpose()?
+                    .unwrap_or(crate::Vec4 { x: 0.0, y: 0.0, z: 0.0, w: 1.0 });
 
-                    crate::Vec3 { x: tr[0].as_f64().unwrap() as f32, y: tr[1].as_f64().unwrap() as f32, z: tr[2].as_f64().unwrap() as f32 }
-                } else {
-                    crate::Vec3 { x: 0.0, y: 0.0, z: 0.0 }
-                };
+                let scale = value.get("scale").map(|sc| sc.vec3()).transpose()?
+                    .unwrap_or(crate::Vec3 { x: 1.0, y: 1.0, z: 1.0 });
 
-                let weights = if let Some(s_weights) = value.get("weights") {
-                    let s_weights = s_weights.as_array().unwrap();
+                let translation = value.get("translation").map(|tr| tr.vec3()).transpose()?
+                    .unwrap_or(crate::Vec3 { x: 0.0, y: 0.0, z: 0.0 });
 
-                    let mut weights = Vec::with_capacity(s_weights.len());
-                    for weight in s_weights {
-                        weights.push(weight.as_f64().unwrap() as f32);
-                    }
+                let weights = value.get("weights").map(|wt| wt.float_array()).transpose()?;
 
-                    Some(weights)
-                } else {
-                    None
-                };
+                let name = value.get_string("name")?;
 
-                let name = if let Some(nm) = value.get("name") {
-                    Some(nm.as_str().unwrap().to_string())
-                } else {
-                    None
-                };
+                let light = value.get("extensions").and_then(|ex| ex.get("KHR_lights_punctual"))
+                    .map(|lt| lt.field("light")?.index(num_lights)).transpose()?;
 
                 nodes.push(Node {
                     camera,
@@ -421,117 +759,59 @@ impl Importer for Gltf {
                     translation,
                     weights,
                     name,
+                    light
                 });
             }
 
-            Some(nodes)
-        } else {
-            None
-        };
-        
-        let materials = if let Some(s_materials) = json.get("materials") {
-            let s_materials = s_materials.as_array().unwrap();
+            Ok(nodes)
+        }).transpose()?;
+
+        let materials = json.get("materials").map(|s_materials| -> Result<_, ImportError> {
+            let s_materials = s_materials.array()?;
 
             let mut materials = Vec::with_capacity(s_materials.len());
             for material in s_materials {
-                let name = if let Some(nm) = material.get("name") {
-                    Some(nm.as_str().unwrap().to_string())
-                } else {
-                    None
-                };
+                let name = material.get_string("name")?;
 
-                let pbr_metallic_roughness = if let Some(pbr) = material.get("pbrMetallicRoughness") {
-                    let base_color_factor = if let Some(bcf) = pbr.get("baseColorFactor") {
-                        crate::Vec4 { x: bcf[0].as_f64().unwrap() as f32, y: bcf[1].as_f64().unwrap() as f32, z: bcf[2].as_f64().unwrap() as f32, w: bcf[3].as_f64().unwrap() as f32 }
-                    } else {
-                        crate::Vec4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 }
-                    };
+                let pbr_metallic_roughness = material.get("pbrMetallicRoughness").map(|pbr| -> Result<_, ImportError> {
+                    let base_color_factor = pbr.get("baseColorFactor").map(|bcf| bcf.vec4()).transpose()?
+                        .unwrap_or(crate::Vec4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 });
 
-                    let base_color_texture = if let Some(bct) = pbr.get("baseColorTexture") {
-                        Some(get_texture_info(bct))
-                    } else {
-                        None
-                    };
-                    
-                    let metallic_factor = if let Some(mf) = pbr.get("metallicFactor") {
-                        mf.as_f64().unwrap() as f32
-                    } else {
-                        1.0
-                    };
+                    let base_color_texture = pbr.get("baseColorTexture").map(|bct| get_texture_info(&bct, num_textures)).transpose()?;
 
-                    let roughness_factor = if let Some(rf) = pbr.get("roughnessFactor") {
-                        rf.as_f64().unwrap() as f32
-                    } else {
-                        1.0
-                    };
+                    let metallic_factor = pbr.get_f32("metallicFactor")?.unwrap_or(1.0);
 
-                    let metallic_roughness_texture = if let Some(mft) = pbr.get("metallicRoughnessTexture") {
-                        Some(get_texture_info(mft))
-                    } else {
-                        None
-                    };
+                    let roughness_factor = pbr.get_f32("roughnessFactor")?.unwrap_or(1.0);
+
+                    let metallic_roughness_texture = pbr.get("metallicRoughnessTexture").map(|mrt| get_texture_info(&mrt, num_textures)).transpose()?;
 
-                    Some(PbrMetallicRoughness {
+                    Ok(PbrMetallicRoughness {
                         base_color_factor,
                         base_color_texture,
                         metallic_factor,
                         roughness_factor,
                         metallic_roughness_texture
                     })
-                } else {
-                    None
-                };
+                }).transpose()?;
 
-                let normal_texture = if let Some(nt) = material.get("normalTexture") {
-                    Some(get_texture_info(nt))
-                } else {
-                    None
-                };
+                let normal_texture = material.get("normalTexture").map(|nt| get_texture_info(&nt, num_textures)).transpose()?;
 
-                let occlusion_texture = if let Some(ot) = material.get("occlusionTexture") {
-                    Some(get_texture_info(ot))
-                } else {
-                    None
-                };
+                let occlusion_texture = material.get("occlusionTexture").map(|ot| get_texture_info(&ot, num_textures)).transpose()?;
 
-                let emissive_texture = if let Some(et) = material.get("emissiveTexture") {
-                    Some(get_texture_info(et))
-                } else {
-                    None
-                };
+                let emissive_texture = material.get("emissiveTexture").map(|et| get_texture_info(&et, num_textures)).transpose()?;
 
-                let emissive_factor = if let Some(ef) = material.get("emissiveFactor") {
-                    let ef = ef.as_array().unwrap();
+                let emissive_factor = material.get("emissiveFactor").map(|ef| ef.vec3()).transpose()?
+                    .unwrap_or(crate::Vec3 { x: 0.0, y: 0.0, z: 0.0 });
 
-                    crate::Vec3 { x: ef[0].as_f64().unwrap() as f32, y: ef[1].as_f64().unwrap() as f32, z: ef[2].as_f64().unwrap() as f32 }
-                } else {
-                    crate::Vec3 { x: 0.0, y: 0.0, z: 0.0 }
-                };
-                
-                let alpha_mode = if let Some(am) = material.get("alphaMode") {
-                    let am = am.as_str().unwrap();
-
-                    match am {
-                        "OPAQUE" => AlphaMode::Opaque,
-                        "MASK" => AlphaMode::Mask,
-                        "BLEND" => AlphaMode::Blend,
-                        _ => AlphaMode::Opaque
-                    }
-                } else {
-                    AlphaMode::Opaque
+                let alpha_mode = match material.get("alphaMode").map(|am| am.str()).transpose()? {
+                    Some("MASK") => AlphaMode::Mask,
+                    Some("BLEND") => AlphaMode::Blend,
+                    _ => AlphaMode::Opaque
                 };
 
-                let alpha_cutoff = if let Some(ac) = material.get("alphaCutoff") {
-                    ac.as_f64().unwrap() as f32
-                } else {
-                    0.5
-                };
+                let alpha_cutoff = material.get_f32("alphaCutoff")?.unwrap_or(0.5);
 
-                let double_sided = if let Some(dc) = material.get("doubleSided") {
-                    dc.as_bool().unwrap()
-                } else {
-                    false
-                };
+                let double_sided = material.get("doubleSided").map(|ds| ds.bool()).transpose()?.unwrap_or(false);
 
                 materials.push(Material {
                     name,
@@ -546,206 +826,143 @@ impl Importer for Gltf {
                 });
             }
 
-            Some(materials)
-        } else {
-            None
-        };
+            Ok(materials)
+        }).transpose()?;
 
-        let meshes = if let Some(s_meshes) = json.get("meshes") {
-            let s_meshes = s_meshes.as_array().unwrap();
+        let meshes = json.get("meshes").map(|s_meshes| -> Result<_, ImportError> {
+            let s_meshes = s_meshes.array()?;
             let mut meshes = Vec::with_capacity(s_meshes.len());
-            
+
             for mesh in s_meshes {
-                let s_primitives = mesh["primitives"].as_array().unwrap();
-                let mut primitives = Vec::with_capacity(s_primitives.len());
+                let s_primitives = mesh.field("primitives")?;
+                let s_primitives = s_primitives.array()?;
+                if s_primitives.is_empty() {
+                    return Err(mesh.invalid("A mesh must have at least one primitive."));
+                }
 
+                let mut primitives = Vec::with_capacity(s_primitives.len());
                 for primitive in s_primitives {
-                    let s_attributes = primitive["attributes"].as_object().unwrap();
-                    let mut attributes = HashMap::with_capacity(s_attributes.len());
+                    let attributes = primitive.field("attributes")?.index_map(num_accessors)?;
 
-                    for (key, value) in s_attributes {
-                        attributes.insert(key.to_string(), value.as_i64().unwrap() as i32);
-                    }
+                    let indices = primitive.get_index("indices", num_accessors)?;
 
-                    let indices = if let Some(id) = primitive.get("indices") {
-                        Some(id.as_i64().unwrap() as i32)
-                    } else {
-                        None
-                    };
+                    let material = primitive.get_index("material", num_materials)?;
 
-                    let material = if let Some(mat) = primitive.get("material") {
-                        Some(mat.as_i64().unwrap() as i32)
-                    } else {
-                        None
+                    let mode = match primitive.get("mode").map(|md| md.i32()).transpose()? {
+                        Some(0) => Topology::Points,
+                        Some(1) => Topology::Lines,
+                        Some(2) => Topology::LineLoop,
+                        Some(3) => Topology::LineStrip,
+                        Some(5) => Topology::TriangleStrip,
+                        Some(6) => Topology::TriangleFan,
+                        _ => Topology::Triangles
                     };
 
-                    let mode = if let Some(md) = primitive.get("mode") {
-                        match md.as_i64().unwrap() {
-                            0 => Topology::Points,
-                            1 => Topology::Lines,
-                            2 => Topology::LineLoop,
-                            3 => Topology::LineStrip,
-                            4 => Topology::Triangles,
-                            5 => Topology::TriangleStrip,
-                            6 => Topology::TriangleFan,
-                            _ => Topology::Triangles
+                    let targets = primitive.get("targets").map(|s_targets| -> Result<_, ImportError> {
+                        s_targets.array()?.iter().map(|target| target.index_map(num_accessors)).collect()
+                    }).transpose()?;
+
+                    let draco = primitive.get("extensions").and_then(|ex| ex.get("KHR_draco_mesh_compression")).map(|dr| -> Result<_, ImportError> {
+                        let s_attributes = dr.field("attributes")?;
+
+                        let mut attributes = HashMap::new();
+                        for (key, value) in s_attributes.object()? {
+                            attributes.insert(key.to_string(), value.non_negative()?);
                         }
-                    } else {
-                        Topology::Triangles
-                    };
+
+                        Ok(DracoCompression {
+                            buffer_view: dr.field("bufferView")?.index(num_buffer_views)?,
+                            attributes
+                        })
+                    }).transpose()?;
 
                     primitives.push(MeshPrimitive {
                         attributes,
                         indices,
                         material,
                         mode,
+                        targets,
+                        draco
                     });
                 }
 
-                let weights = if let Some(s_weights) = mesh.get("weights") {
-                    let s_weights = s_weights.as_array().unwrap();
+                let weights = mesh.get("weights").map(|wt| wt.float_array()).transpose()?;
 
-                    let mut weights = Vec::with_capacity(s_weights.len());
-                    for weight in s_weights {
-                        weights.push(weight.as_f64().unwrap() as f32);
-                    }
-
-                    Some(weights)
-                } else {
-                    None
-                };
+                let name = mesh.get_string("name")?;
 
-                let name = if let Some(nm) = mesh.get("name") {
-                    Some(nm.as_str().unwrap().to_string())
-                } else {
-                    None
-                };
+                // Extras are application specific, so don't fail if they're not in the shape we expect.
+                // Non-string names become empty, so the names stay aligned with their targets.
+                let target_names = mesh.value.get("extras").and_then(|ex| ex.get("targetNames")).and_then(|tn| tn.as_array())
+                    .map(|tn| tn.iter().map(|name| name.as_str().unwrap_or_default().to_string()).collect());
 
                 meshes.push(Mesh {
                     primitives,
                     weights,
-                    name
+                    name,
+                    target_names
                 });
             }
 
-            Some(meshes)
-        } else {
-            None
-        };
+            Ok(meshes)
+        }).transpose()?;
 
-        let textures = if let Some(s_textures) = json.get("textures") {
-            let s_textures = s_textures.as_array().unwrap();
+        let textures = json.get("textures").map(|s_textures| -> Result<_, ImportError> {
+            let s_textures = s_textures.array()?;
 
             let mut textures = Vec::with_capacity(s_textures.len());
             for texture in s_textures {
-                let sampler = if let Some(smp) = texture.get("sampler") {
-                    Some(smp.as_i64().unwrap() as i32)
-                } else {
-                    None
-                };
-
-                let source = if let Some(src) = texture.get("source") {
-                    Some(src.as_i64().unwrap() as i32)
-                } else {
-                    None
-                };
-
-                let name = if let Some(nm) = texture.get("name") {
-                    Some(nm.as_str().unwrap().to_string())
-                } else {
-                    None 
-                };
-
                 textures.push(Texture {
-                    sampler,
-                    source,
-                    name
+                    sampler: texture.get_index("sampler", num_samplers)?,
+                    source: texture.get_index("source", num_images)?,
+                    name: texture.get_string("name")?
                 });
             }
 
-            Some(textures)
-        } else {
-            None
-        };
+            Ok(textures)
+        }).transpose()?;
 
-        let images = if let Some(s_images) = json.get("images") {
-            let s_images = s_images.as_array().unwrap();
+        let images = json.get("images").map(|s_images| -> Result<_, ImportError> {
+            let s_images = s_images.array()?;
 
             let mut images = Vec::with_capacity(s_images.len());
             for image in s_images {
-                let uri = if let Some(ui) = image.get("uri") {
-                    Some(ui.as_str().unwrap().to_string())
-                } else {
-                    None
-                };
-
-                let mime_type = if let Some(mt) = image.get("mimeType") {
-                    Some(mt.as_str().unwrap().to_string())
-                } else {
-                    None
-                };
-
-                let buffer_view = if let Some(bv) = image.get("bufferView") {
-                    Some(bv.as_i64().unwrap() as i32)
-                } else {
-                    None
-                };
-
-                let name = if let Some(nm) = image.get("name") {
-                    Some(nm.as_str().unwrap().to_string())
-                } else {
-                    None
-                };
-
                 images.push(Image {
-                    uri,
-                    mime_type,
-                    buffer_view,
-                    name
+                    uri: image.get_string("uri")?,
+                    mime_type: image.get_string("mimeType")?,
+                    buffer_view: image.get_index("bufferView", num_buffer_views)?,
+                    name: image.get_string("name")?
                 });
             }
 
-            Some(images)
-        } else {
-            None
-        };
+            Ok(images)
+        }).transpose()?;
 
-        let accessors = if let Some(s_accessors) = json.get("accessors") {
-            let s_accessors = s_accessors.as_array().unwrap();
+        let mut accessors = json.get("accessors").map(|s_accessors| -> Result<_, ImportError> {
+            let s_accessors = s_accessors.array()?;
 
             let mut accessors = Vec::with_capacity(s_accessors.len());
             for accessor in s_accessors {
-                let buffer_view = if let Some(bv) = accessor.get("bufferView") {
-                    Some(bv.as_i64().unwrap() as i32)
-                } else {
-                    None
-                };
+                let buffer_view = accessor.get_index("bufferView", num_buffer_views)?;
 
-                let byte_offset = if let Some(bo) = accessor.get("byteOffset") {
-                    bo.as_i64().unwrap() as i32
-                } else {
-                    0
-                };
+                let byte_offset = accessor.get("byteOffset").map(|bo| bo.non_negative()).transpose()?.unwrap_or(0);
 
-                let component_type = match accessor["componentType"].as_i64().unwrap() {
+                let s_component_type = accessor.field("componentType")?;
+                let component_type = match s_component_type.i32()? {
                     5120 => ComponentType::Byte,
                     5121 => ComponentType::UnsignedByte,
                     5122 => ComponentType::Short,
                     5123 => ComponentType::UnsignedShort,
                     5125 => ComponentType::UnsignedInt,
                     5126 => ComponentType::Float,
-                    ct => panic!("Unrecognized component type {ct}")
+                    ct => return Err(s_component_type.invalid(format!("Unrecognized component type {ct}.")))
                 };
 
-                let normalized = if let Some(nm) = accessor.get("normalized") {
-                    nm.as_bool().unwrap()
-                } else {
-                    false
-                };
+                let normalized = accessor.get("normalized").map(|nm| nm.bool()).transpose()?.unwrap_or(false);
 
-                let count = accessor["count"].as_i64().unwrap() as i32;
-                
-                let accessor_type = match accessor["type"].as_str().unwrap() {
+                let count = accessor.field("count")?.non_negative()?;
+
+                let s_accessor_type = accessor.field("type")?;
+                let accessor_type = match s_accessor_type.str()? {
                     "SCALAR" => AccessorType::Scalar,
                     "VEC2" => AccessorType::Vec2,
                     "VEC3" => AccessorType::Vec3,
@@ -753,90 +970,47 @@ impl Importer for Gltf {
                     "MAT2" => AccessorType::Mat2,
                     "MAT3" => AccessorType::Mat3,
                     "MAT4" => AccessorType::Mat4,
-                    at => panic!("Unrecognized accessor type \"{at}\".")
+                    at => return Err(s_accessor_type.invalid(format!("Unrecognized accessor type \"{at}\".")))
                 };
 
-                let max = if let Some(mx) = accessor.get("max") {
-                    let mx = mx.as_array().unwrap();
-                    
-                    let mut max = Vec::with_capacity(mx.len());
-                    for value in mx {
-                        max.push(value.as_f64().unwrap() as f32);
-                    }
+                let max = accessor.get("max").map(|mx| mx.float_array()).transpose()?;
 
-                    Some(max)
-                } else {
-                    None
-                };
+                let min = accessor.get("min").map(|mn| mn.float_array()).transpose()?;
 
-                let min = if let Some(mn) = accessor.get("min") {
-                    let mn = mn.as_array().unwrap();
-                    
-                    let mut min = Vec::with_capacity(mn.len());
-                    for value in mn {
-                        min.push(value.as_f64().unwrap() as f32);
-                    }
+                let sparse = accessor.get("sparse").map(|s_sparse| -> Result<_, ImportError> {
+                    let count = s_sparse.field("count")?.non_negative()?;
 
-                    Some(min)
-                } else {
-                    None
-                };
-
-                let sparse = if let Some(s_sparce) = accessor.get("sparse") {
-                    let count = s_sparce["count"].as_i64().unwrap() as i32;
-
-                    let s_indices = &s_sparce["indices"];
-
-                    let buffer_view = s_indices["bufferView"].as_i64().unwrap() as i32;
+                    let s_indices = s_sparse.field("indices")?;
 
-                    let byte_offset = if let Some(bo) = s_indices.get("byteOffset") {
-                        bo.as_i64().unwrap() as i32
-                    } else {
-                        0
-                    };
-
-                    let component_type = match s_indices["componentType"].as_i64().unwrap() {
+                    let s_component_type = s_indices.field("componentType")?;
+                    let component_type = match s_component_type.i32()? {
                         5121 => ComponentType::UnsignedByte,
                         5123 => ComponentType::UnsignedShort,
                         5125 => ComponentType::UnsignedInt,
-                        ct => panic!("Unsupported component type {ct}.")
+                        ct => return Err(s_component_type.invalid(format!("Unrecognized sparse index component type {ct}.")))
                     };
 
                     let indices = AccessorSparseIndices {
-                        buffer_view,
-                        byte_offset,
+                        buffer_view: s_indices.field("bufferView")?.index(num_buffer_views)?,
+                        byte_offset: s_indices.get("byteOffset").map(|bo| bo.non_negative()).transpose()?.unwrap_or(0),
                         component_type
                     };
 
-                    let s_values = &s_sparce["values"];
-
-                    let buffer_view = s_values["bufferView"].as_i64().unwrap() as i32;
-
-                    let byte_offset = if let Some(bo) = s_values.get("byteOffset") {
-                        bo.as_i64().unwrap() as i32
-                    } else {
-                        0
-                    };
+                    let s_values = s_sparse.field("values")?;
 
                     let values = AccessorSparseValues {
-                        buffer_view,
-                        byte_offset
+                        buffer_view: s_values.field("bufferView")?.index(num_buffer_views)?,
+                        byte_offset: s_values.get("byteOffset").map(|bo| bo.non_negative()).transpose()?.unwrap_or(0)
                     };
 
-                    Some(AccessorSparse {
+                    Ok(AccessorSparse {
                         count,
                         indices,
                         values
                     })
-                } else {
-                    None
-                };
+                }).transpose()?;
 
-                let name = if let Some(nm) = accessor.get("name") {
-                    Some(nm.as_str().unwrap().to_string())
-                } else {
-                    None
-                };
+                let name = accessor.get_string("name")?;
 
                 accessors.push(Accessor {
                     buffer_view,
@@ -852,47 +1026,57 @@ impl Importer for Gltf {
                 });
             }
 
-            Some(accessors)
-        } else {
-            None
-        };
+            Ok(accessors)
+        }).transpose()?;
 
-        let buffer_views = if let Some(s_buffer_views) = json.get("bufferViews") {
-            let s_buffer_views = s_buffer_views.as_array().unwrap();
+        let mut buffer_views = json.get("bufferViews").map(|s_buffer_views| -> Result<_, ImportError> {
+            let s_buffer_views = s_buffer_views.array()?;
 
             let mut buffer_views = Vec::with_capacity(s_buffer_views.len());
-            for view in s_buffer_views { 
-                let buffer = view["buffer"].as_i64().unwrap() as i32;
-                
-                let byte_offset = if let Some(bo) = view.get("byteOffset") {
-                    bo.as_i64().unwrap() as i32
-                } else {
-                    0
-                };
+            for view in s_buffer_views {
+                let buffer = view.field("buffer")?.index(num_buffers)?;
 
-                let byte_length = view["byteLength"].as_i64().unwrap() as i32;
+                let byte_offset = view.get("byteOffset").map(|bo| bo.non_negative()).transpose()?.unwrap_or(0);
 
-                let byte_stride = if let Some(bs) = view.get("byteStride") {
-                    Some(bs.as_i64().unwrap() as i32)
-                } else {
-                    None
-                };
+                let byte_length = view.field("byteLength")?.non_negative()?;
 
-                let target = if let Some(tg) = view.get("target") {
-                    Some(match tg.as_i64().unwrap() {
-                        34962 => Target::ArrayBuffer,
-                        34963 => Target::ElementArrayBuffer,
-                        tg => panic!("Unrecognized target {tg}.")
-                    })
-                } else {
-                    None
-                };
+                let byte_stride = view.get("byteStride").map(|bs| bs.non_negative()).transpose()?;
 
-                let name = if let Some(nm) = view.get("name") {
-                    Some(nm.as_str().unwrap().to_string())
-                } else {
-                    None
-                };
+                let target = view.get("target").map(|tg| match tg.i32()? {
+                    34962 => Ok(Target::ArrayBuffer),
+                    34963 => Ok(Target::ElementArrayBuffer),
+                    t => Err(tg.invalid(format!("Unrecognized target {t}.")))
+                }).transpose()?;
+
+                let name = view.get_string("name")?;
+
+                let meshopt = view.get("extensions").and_then(|ex| ex.get("EXT_meshopt_compression")).map(|mo| -> Result<_, ImportError> {
+                    let s_mode = mo.field("mode")?;
+                    let mode = match s_mode.str()? {
+                        "ATTRIBUTES" => MeshoptMode::Attributes,
+                        "TRIANGLES" => MeshoptMode::Triangles,
+                        "INDICES" => MeshoptMode::Indices,
+                        md => return Err(s_mode.invalid(format!("Unrecognized meshopt mode \"{md}\".")))
+                    };
+
+                    let filter = match mo.get("filter").map(|fl| fl.str()).transpose()?.unwrap_or("NONE") {
+                        "NONE" => MeshoptFilter::None,
+                        "OCTAHEDRAL" => MeshoptFilter::Octahedral,
+                        "QUATERNION" => MeshoptFilter::Quaternion,
+                        "EXPONENTIAL" => MeshoptFilter::Exponential,
+                        fl => return Err(mo.invalid(format!("Unrecognized meshopt filter \"{fl}\".")))
+                    };
+
+                    Ok(MeshoptCompression {
+                        buffer: mo.field("buffer")?.index(num_buffers)?,
+                        byte_offset: mo.get("byteOffset").map(|bo| bo.non_negative()).transpose()?.unwrap_or(0),
+                        byte_length: mo.field("byteLength")?.non_negative()?,
+                        byte_stride: mo.field("byteStride")?.non_negative()?,
+                        count: mo.field("count")?.non_negative()?,
+                        mode,
+                        filter
+                    })
+                }).transpose()?;
 
                 buffer_views.push(BufferView {
                     buffer,
@@ -900,110 +1084,231 @@ impl Importer for Gltf {
                     byte_length,
                     byte_stride,
                     target,
-                    name
+                    name,
+                    meshopt
                 });
             }
 
-            Some(buffer_views)
-        } else {
-            None
-        };
+            Ok(buffer_views)
+        }).transpose()?;
 
-        let samplers = if let Some(s_samplers) = json.get("samplers") {
-            let s_samplers = s_samplers.as_array().unwrap();
+        let samplers = json.get("samplers").map(|s_samplers| -> Result<_, ImportError> {
+            let s_samplers = s_samplers.array()?;
 
             let mut samplers = Vec::with_capacity(s_samplers.len());
             for sampler in s_samplers {
-                let mag_filter = if let Some(mf) = sampler.get("magFilter") {
-                    Some(get_texture_filter(mf.as_i64().unwrap()))
-                } else {
-                    None
-                };
+                samplers.push(Sampler {
+                    mag_filter: sampler.get("magFilter").map(|mf| get_texture_filter(&mf)).transpose()?,
+                    min_filter: sampler.get("minFilter").map(|mf| get_texture_filter(&mf)).transpose()?,
+                    wrap_s: sampler.get("wrapS").map(|ws| get_wrap_mode(&ws)).transpose()?.unwrap_or(TextureWrapMode::Repeat),
+                    wrap_t: sampler.get("wrapT").map(|wt| get_wrap_mode(&wt)).transpose()?.unwrap_or(TextureWrapMode::Repeat),
+                    name: sampler.get_string("name")?
+                });
+            }
 
-                let min_filter = if let Some(mf) = sampler.get("minFilter") {
-                    Some(get_texture_filter(mf.as_i64().unwrap()))
-                } else {
-                    None
-                };
+            Ok(samplers)
+        }).transpose()?;
 
-                let wrap_s = if let Some(ws) = sampler.get("wrapS") {
-                    get_wrap_mode(ws.as_i64().unwrap())
-                } else {
-                    TextureWrapMode::Repeat
-                };
+        let skins = json.get("skins").map(|s_skins| -> Result<_, ImportError> {
+            let s_skins = s_skins.array()?;
 
-                let wrap_t = if let Some(wt) = sampler.get("wrapT") {
-                    get_wrap_mode(wt.as_i64().unwrap())
-                } else {
-                    TextureWrapMode::Repeat
-                };
+            let mut skins = Vec::with_capacity(s_skins.len());
+            for skin in s_skins {
+                skins.push(Skin {
+                    inverse_bind_matrices: skin.get_index("inverseBindMatrices", num_accessors)?,
+                    skeleton: skin.get_index("skeleton", num_nodes)?,
+                    joints: skin.field("joints")?.indices(num_nodes)?,
+                    name: skin.get_string("name")?
+                });
+            }
 
-                let name = if let Some(nm) = sampler.get("name") {
-                    Some(nm.as_str().unwrap().to_string())
-                } else {
-                    None
+            Ok(skins)
+        }).transpose()?;
+
+        let animations = json.get("animations").map(|s_animations| -> Result<_, ImportError> {
+            let s_animations = s_animations.array()?;
+
+            let mut animations = Vec::with_capacity(s_animations.len());
+            for animation in s_animations {
+                let s_samplers = animation.field("samplers")?;
+                let s_samplers = s_samplers.array()?;
+                let mut samplers = Vec::with_capacity(s_samplers.len());
+
+                for sampler in &s_samplers {
+                    let input = sampler.field("input")?.index(num_accessors)?;
+
+                    let interpolation = match sampler.get("interpolation").map(|ip| ip.str()).transpose()? {
+                        None | Some("LINEAR") => Interpolation::Linear,
+                        Some("STEP") => Interpolation::Step,
+                        Some("CUBICSPLINE") => Interpolation::CubicSpline,
+                        Some(ip) => return Err(sampler.invalid(format!("Unrecognized interpolation \"{ip}\".")))
+                    };
+
+                    let output = sampler.field("output")?.index(num_accessors)?;
+
+                    samplers.push(AnimationSampler {
+                        input,
+                        interpolation,
+                        output
+                    });
+                }
+
+                let s_channels = animation.field("channels")?;
+                let s_channels = s_channels.array()?;
+                let mut channels = Vec::with_capacity(s_channels.len());
+
+                for channel in s_channels {
+                    let sampler = channel.field("sampler")?.index(s_samplers.len())?;
+
+                    let s_target = channel.field("target")?;
+
+                    let node = s_target.get_index("node", num_nodes)?;
+
+                    let s_path = s_target.field("path")?;
+                    let path = match s_path.str()? {
+                        "translation" => AnimationPath::Translation,
+                        "rotation" => AnimationPath::Rotation,
+                        "scale" => AnimationPath::Scale,
+                        "weights" => AnimationPath::Weights,
+                        pt => return Err(s_path.invalid(format!("Unrecognized target path \"{pt}\".")))
+                    };
+
+                    channels.push(AnimationChannel {
+                        sampler,
+                        target: AnimationChannelTarget {
+                            node,
+                            path
+                        }
+                    });
+                }
+
+                let name = animation.get_string("name")?;
+
+                animations.push(Animation {
+                    channels,
+                    samplers,
+                    name
+                });
+            }
+
+            Ok(animations)
+        }).transpose()?;
+
+        let cameras = json.get("cameras").map(|s_cameras| -> Result<_, ImportError> {
+            let s_cameras = s_cameras.array()?;
+
+            let mut cameras = Vec::with_capacity(s_cameras.len());
+            for camera in s_cameras {
+                let s_camera_type = camera.field("type")?;
+                let camera_type = match s_camera_type.str()? {
+                    "perspective" => {
+                        let s_perspective = camera.field("perspective")?;
+
+                        // A missing zfar means the camera uses an infinite projection.
+                        CameraType::Perspective(CameraPerspective {
+                            aspect_ratio: s_perspective.get_f32("aspectRatio")?,
+                            yfov: s_perspective.field("yfov")?.f32()?,
+                            zfar: s_perspective.get_f32("zfar")?,
+                            znear: s_perspective.field("znear")?.f32()?
+                        })
+                    },
+
+                    "orthographic" => {
+                        let s_orthographic = camera.field("orthographic")?;
+
+                        CameraType::Orthographic(CameraOrthographic {
+                            xmag: s_orthographic.field("xmag")?.f32()?,
+                            ymag: s_orthographic.field("ymag")?.f32()?,
+                            zfar: s_orthographic.field("zfar")?.f32()?,
+                            znear: s_orthographic.field("znear")?.f32()?
+                        })
+                    },
+
+                    ct => return Err(s_camera_type.invalid(format!("Unrecognized camera type \"{ct}\".")))
                 };
 
-                samplers.push(Sampler {
-                    mag_filter,
-                    min_filter,
-                    wrap_s,
-                    wrap_t,
-                    name,
+                let name = camera.get_string("name")?;
+
+                cameras.push(Camera {
+                    camera_type,
+                    name
                 });
             }
 
-            Some(samplers)
-        } else {
-            None
-        };
+            Ok(cameras)
+        }).transpose()?;
+
+        let lights = json.get("extensions").and_then(|ex| ex.get("KHR_lights_punctual")).map(|s_lights| -> Result<_, ImportError> {
+            let s_lights = s_lights.field("lights")?;
+            let s_lights = s_lights.array()?;
+
+            let mut lights = Vec::with_capacity(s_lights.len());
+            for light in s_lights {
+                let color = light.get("color").map(|cl| cl.vec3()).transpose()?
+                    .unwrap_or(crate::Vec3 { x: 1.0, y: 1.0, z: 1.0 });
+
+                let intensity = light.get_f32("intensity")?.unwrap_or(1.0);
+
+                let s_light_type = light.field("type")?;
+                let light_type = match s_light_type.str()? {
+                    "directional" => LightType::Directional,
+                    "point" => LightType::Point,
+                    "spot" => {
+                        let s_spot = light.field("spot")?;
+
+                        LightType::Spot(LightSpot {
+                            inner_cone_angle: s_spot.get_f32("innerConeAngle")?.unwrap_or(0.0),
+                            outer_cone_angle: s_spot.get_f32("outerConeAngle")?.unwrap_or(std::f32::consts::FRAC_PI_4)
+                        })
+                    },
+                    lt => return Err(s_light_type.invalid(format!("Unrecognized light type \"{lt}\".")))
+                };
+
+                // No range means the light has infinite range.
+                let range = light.get_f32("range")?;
 
-        let directory = Path::new(path).parent().unwrap();
+                let name = light.get_string("name")?;
 
-        let buffers = if let Some(s_buffers) = json.get("buffers") {
-            let s_buffers = s_buffers.as_array().unwrap();
+                lights.push(Light {
+                    color,
+                    intensity,
+                    light_type,
+                    range,
+                    name
+                });
+            }
+
+            Ok(lights)
+        }).transpose()?;
+
+        let mut buffers = json.get("buffers").map(|s_buffers| -> Result<_, ImportError> {
+            let s_buffers = s_buffers.array()?;
 
             let mut buffers = Vec::with_capacity(s_buffers.len());
             for buffer in s_buffers {
-                let data = if let Some(ui) = buffer.get("uri") {
-                    let uri = ui.as_str().unwrap();
-                    
+                // Fallback buffers only exist for loaders without meshopt support, so they are never read.
+                let is_fallback = buffer.get("extensions").and_then(|ex| ex.get("EXT_meshopt_compression"))
+                    .and_then(|mo| mo.get("fallback")).map(|fb| fb.bool()).transpose()?.unwrap_or(false);
+
+                let data = if let Some(s_uri) = buffer.get("uri") {
+                    let uri = s_uri.str()?;
+
                     // This is an embedded data type.
-                    if uri.starts_with("data:") {
-                        // Finds the end of the "application/xyz" string.
-                        // The glTF spec says that this *must* be present.
-                        let location = uri.find(';').unwrap() + 1;
-                        let (is_base64, other_location) = match uri.find(',') {
-                            Some(loc) => {
-                                if &uri[location..loc] == "base64" {
-                                    (true, loc + 1)
-                                } else {
-                                    (false, 0)
-                                }
-                            },
-                            None => (false, 0),
-                        };
-
-                        if is_base64 {
-                            base64::engine::general_purpose::STANDARD.decode(&uri[other_location..]).unwrap()
-                        } else {
-                            uri[other_location..].as_bytes().to_vec()
-                        }
+                    if let Some(data) = uri.strip_prefix("data:") {
+                        decode_data_uri(data).map_err(|message| s_uri.invalid(message))?.1
                     } else {
-                        std::fs::read(directory.join(uri))?
+                        // URIs are stored percent-encoded, such as "My%20Model.bin".
+                        resolver.resolve(base, &String::from_utf8_lossy(&percent_decode(uri)))
+                            .map_err(|error| ImportError::Resource { path: s_uri.path.clone(), error })?
                     }
+                } else if is_fallback {
+                    Vec::new()
                 } else {
                     if !is_glb {
-                        return Err(io::Error::new(io::ErrorKind::InvalidData, "No buffer URI was provided, but the file is not a GLB file."));
+                        return Err(buffer.invalid("No buffer URI was provided, but the file is not a GLB file."));
                     }
 
-                    let bin_length = reader.read_u32();
-                    
-                    if reader.read_u32() != 0x004E4942 {
-                        return Err(io::Error::new(io::ErrorKind::InvalidData, "Expected BIN, did not find BIN."));
-                    }
-
-                    reader.read_bytes(bin_length as usize).to_vec()
+                    bin.take().ok_or_else(|| buffer.invalid("No buffer URI was provided, but the GLB has no unused BIN chunk."))?.to_vec()
                 };
 
                 buffers.push(Buffer {
@@ -1011,10 +1316,16 @@ impl Importer for Gltf {
                 });
             }
 
-            Some(buffers)
-        } else {
-            None
-        };
+            Ok(buffers)
+        }).transpose()?;
+
+        if let (Some(buffer_views), Some(buffers)) = (&mut buffer_views, &mut buffers) {
+            decode_meshopt_views(buffer_views, buffers)?;
+        }
+
+        if let Some(meshes) = &meshes {
+            decode_draco_primitives(meshes, &mut accessors, &mut buffer_views, &mut buffers)?;
+        }
 
         Ok(Gltf {
             asset,
@@ -1028,43 +1339,422 @@ impl Importer for Gltf {
             accessors,
             buffer_views,
             samplers,
+            skins,
+            animations,
+            cameras,
+            lights,
             buffers
         })
     }
 }
 
-fn get_texture_info(value: &Value) -> TextureInfo {
-    let index = value["index"].as_i64().unwrap() as i32;
-    let tex_coord = if let Some(tc) = value.get("texCoord") { tc.as_i64().unwrap() as i32 } else { 0 };
+/// Decodes the part of a data URI after "data:" into its media type and its data.
+fn decode_data_uri(uri: &str) -> Result<(&str, Vec<u8>), String> {
+    // The glTF spec says that the "application/xyz" string *must* be present.
+    let (media_type, data) = uri.split_once(',').ok_or("Data URI has no data.")?;
+
+    match media_type.strip_suffix(";base64") {
+        Some(media_type) => {
+            let data = base64::engine::general_purpose::STANDARD.decode(data).map_err(|e| format!("Data URI is not valid base64: {e}"))?;
+            Ok((media_type, data))
+        },
+
+        None => Ok((media_type, percent_decode(data)))
+    }
+}
+
+/// Splits a GLB file into its JSON chunk and its BIN chunk, if it has one.
+fn read_glb(data: &[u8]) -> Result<(&[u8], Option<&[u8]>), ImportError> {
+    let invalid = |message: &str| ImportError::InvalidData { path: String::new(), message: message.to_string() };
+    let read_u32 = |offset: usize| data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+
+    // The header is the magic, the version, and the total length.
+    let mut offset = 12;
+    let mut chunks = Vec::new();
+    while offset < data.len() {
+        let (Some(length), Some(chunk_type)) = (read_u32(offset), read_u32(offset + 4)) else {
+            return Err(invalid("GLB chunk header is truncated."));
+        };
+
+        let chunk = data.get(offset + 8..offset + 8 + length as usize).ok_or_else(|| invalid("GLB chunk is truncated."))?;
+        chunks.push((chunk_type, chunk));
+        offset += 8 + length as usize;
+    }
+
+    let json = match chunks.first() {
+        Some(&(0x4E4F534A, json)) => json,
+        _ => return Err(invalid("Expected JSON, did not find JSON."))
+    };
+
+    // Any chunks other than BIN are extensions, and can be ignored.
+    let bin = match chunks.get(1) {
+        Some(&(0x004E4942, bin)) => Some(bin),
+        _ => None
+    };
+
+    Ok((json, bin))
+}
+
+/// A JSON value along with its path in the document, so errors can point at the element that caused them.
+struct Json<'a> {
+    value: &'a Value,
+    path:  String
+}
+
+impl<'a> Json<'a> {
+    fn new(value: &'a Value) -> Json<'a> {
+        Json { value, path: String::new() }
+    }
+
+    fn child_path(&self, key: &str) -> String {
+        if self.path.is_empty() { key.to_string() } else { format!("{}.{key}", self.path) }
+    }
+
+    fn get(&self, key: &str) -> Option<Json<'a>> {
+        self.value.get(key).map(|value| Json { value, path: self.child_path(key) })
+    }
+
+    fn field(&self, key: &str) -> Result<Json<'a>, ImportError> {
+        self.get(key).ok_or_else(|| ImportError::MissingField { path: self.child_path(key) })
+    }
+
+    fn invalid(&self, message: impl Into<String>) -> ImportError {
+        ImportError::InvalidData { path: self.path.clone(), message: message.into() }
+    }
+
+    fn invalid_type(&self, expected: &'static str) -> ImportError {
+        ImportError::InvalidType { path: self.path.clone(), expected }
+    }
+
+    fn array(&self) -> Result<Vec<Json<'a>>, ImportError> {
+        let array = self.value.as_array().ok_or_else(|| self.invalid_type("an array"))?;
+        Ok(array.iter().enumerate().map(|(i, value)| Json { value, path: format!("{}[{i}]", self.path) }).collect())
+    }
+
+    fn object(&self) -> Result<Vec<(&'a str, Json<'a>)>, ImportError> {
+        let object = self.value.as_object().ok_or_else(|| self.invalid_type("an object"))?;
+        Ok(object.iter().map(|(key, value)| (key.as_str(), Json { value, path: self.child_path(key) })).collect())
+    }
+
+    fn str(&self) -> Result<&'a str, ImportError> {
+        self.value.as_str().ok_or_else(|| self.invalid_type("a string"))
+    }
+
+    fn string(&self) -> Result<String, ImportError> {
+        self.str().map(str::to_string)
+    }
+
+    fn bool(&self) -> Result<bool, ImportError> {
+        self.value.as_bool().ok_or_else(|| self.invalid_type("a boolean"))
+    }
+
+    fn f32(&self) -> Result<f32, ImportError> {
+        self.value.as_f64().map(|v| v as f32).ok_or_else(|| self.invalid_type("a number"))
+    }
+
+    fn i32(&self) -> Result<i32, ImportError> {
+        self.value.as_i64().and_then(|v| i32::try_from(v).ok()).ok_or_else(|| self.invalid_type("an integer"))
+    }
+
+    fn non_negative(&self) -> Result<i32, ImportError> {
+        self.i32().ok().filter(|&v| v >= 0).ok_or_else(|| self.invalid_type("a non-negative integer"))
+    }
+
+    /// Reads an index into an array of `len` elements.
+    fn index(&self, len: usize) -> Result<i32, ImportError> {
+        let index = self.value.as_i64().ok_or_else(|| self.invalid_type("an index"))?;
+        if index < 0 || index as usize >= len {
+            return Err(ImportError::InvalidIndex { path: self.path.clone(), index });
+        }
+
+        Ok(index as i32)
+    }
+
+    fn indices(&self, len: usize) -> Result<Vec<i32>, ImportError> {
+        self.array()?.iter().map(|value| value.index(len)).collect()
+    }
+
+    /// Reads an object whose values are all indices, such as a primitive's attributes.
+    fn index_map(&self, len: usize) -> Result<HashMap<String, i32>, ImportError> {
+        self.object()?.into_iter().map(|(key, value)| Ok((key.to_string(), value.index(len)?))).collect()
+    }
+
+    fn float_array(&self) -> Result<Vec<f32>, ImportError> {
+        self.array()?.iter().map(|value| value.f32()).collect()
+    }
+
+    fn floats<const N: usize>(&self) -> Result<[f32; N], ImportError> {
+        self.float_array()?.try_into().map_err(|_| self.invalid_type(match N {
+            2 => "an array of 2 numbers",
+            3 => "an array of 3 numbers",
+            4 => "an array of 4 numbers",
+            16 => "an array of 16 numbers",
+            _ => "an array of numbers"
+        }))
+    }
+
+    fn vec2(&self) -> Result<crate::Vec2, ImportError> {
+        self.floats().map(|[x, y]| crate::Vec2 { x, y })
+    }
+
+    fn vec3(&self) -> Result<crate::Vec3, ImportError> {
+        self.floats().map(|[x, y, z]| crate::Vec3 { x, y, z })
+    }
+
+    fn vec4(&self) -> Result<crate::Vec4, ImportError> {
+        self.floats().map(|[x, y, z, w]| crate::Vec4 { x, y, z, w })
+    }
+
+    fn get_string(&self, key: &str) -> Result<Option<String>, ImportError> {
+        self.get(key).map(|value| value.string()).transpose()
+    }
+
+    fn get_f32(&self, key: &str) -> Result<Option<f32>, ImportError> {
+        self.get(key).map(|value| value.f32()).transpose()
+    }
+
+    fn get_index(&self, key: &str, len: usize) -> Result<Option<i32>, ImportError> {
+        self.get(key).map(|value| value.index(len)).transpose()
+    }
+}
+
+/// Converts an error from one of the decoders into an import error at `path`.
+fn decoder_error(path: &str) -> impl Fn(io::Error) -> ImportError + '_ {
+    move |error| {
+        let message = error.to_string();
+        match error.kind() {
+            io::ErrorKind::Unsupported => ImportError::Unsupported { path: path.to_string(), message },
+            _ => ImportError::InvalidData { path: path.to_string(), message }
+        }
+    }
+}
+
+/// Gets `length` bytes at `offset` in a buffer, if they are in range.
+fn buffer_range(buffers: &[Buffer], buffer: i32, offset: i32, length: i32) -> Option<&[u8]> {
+    let start = offset as usize;
+    buffers.get(buffer as usize)?.data.get(start..start.checked_add(length as usize)?)
+}
+
+/// Decodes every meshopt compressed buffer view into a new buffer, and points the view at it.
+fn decode_meshopt_views(buffer_views: &mut [BufferView], buffers: &mut Vec<Buffer>) -> Result<(), ImportError> {
+    for (v, view) in buffer_views.iter_mut().enumerate() {
+        let Some(meshopt) = &view.meshopt else {
+            continue;
+        };
+
+        let path = format!("bufferViews[{v}].extensions.EXT_meshopt_compression");
+        let data = buffer_range(buffers, meshopt.buffer, meshopt.byte_offset, meshopt.byte_length)
+            .ok_or_else(|| ImportError::InvalidData { path: path.clone(), message: "Compressed data is out of range of its buffer.".to_string() })?;
+
+        let count = meshopt.count as usize;
+        let stride = meshopt.byte_stride as usize;
+
+        let mut decoded = match meshopt.mode {
+            MeshoptMode::Attributes => meshopt::decode_vertex_buffer(data, count, stride),
+            MeshoptMode::Triangles => meshopt::decode_index_buffer(data, count).and_then(|indices| index_bytes(&indices, stride)),
+            MeshoptMode::Indices => meshopt::decode_index_sequence(data, count).and_then(|indices| index_bytes(&indices, stride))
+        }.map_err(decoder_error(&path))?;
+
+        match meshopt.filter {
+            MeshoptFilter::None => Ok(()),
+            MeshoptFilter::Octahedral => meshopt::filter_octahedral(&mut decoded, stride),
+            MeshoptFilter::Quaternion => meshopt::filter_quaternion(&mut decoded, stride),
+            MeshoptFilter::Exponential => meshopt::filter_exponential(&mut decoded, stride)
+        }.map_err(decoder_error(&path))?;
+
+        view.buffer = buffers.len() as i32;
+        view.byte_offset = 0;
+        view.byte_length = decoded.len() as i32;
+
+        buffers.push(Buffer {
+            data: decoded
+        });
+    }
+
+    Ok(())
+}
+
+fn index_bytes(indices: &[u32], stride: usize) -> Result<Vec<u8>, io::Error> {
+    match stride {
+        2 => Ok(indices.iter().flat_map(|&i| (i as u16).to_le_bytes()).collect()),
+        4 => Ok(indices.iter().flat_map(|&i| i.to_le_bytes()).collect()),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Meshopt indices must have a stride of 2 or 4."))
+    }
+}
+
+/// Decodes every Draco compressed primitive into a new buffer, and points its accessors at the decoded data.
+fn decode_draco_primitives(meshes: &[Mesh], accessors: &mut Option<Vec<Accessor>>, buffer_views: &mut Option<Vec<BufferView>>,
+                           buffers: &mut Option<Vec<Buffer>>) -> Result<(), ImportError> {
+    for (m, mesh) in meshes.iter().enumerate() {
+        for (p, primitive) in mesh.primitives.iter().enumerate() {
+            let Some(draco) = &primitive.draco else {
+                continue;
+            };
+
+            let path = format!("meshes[{m}].primitives[{p}].extensions.KHR_draco_mesh_compression");
+            let invalid = |message: String| ImportError::InvalidData { path: path.clone(), message };
+
+            // The indices were checked when they were read, so these always exist.
+            let (Some(accessors), Some(buffer_views), Some(buffers)) = (accessors.as_mut(), buffer_views.as_mut(), buffers.as_mut()) else {
+                return Err(invalid("The primitive is Draco compressed, but there are no buffers.".to_string()));
+            };
+
+            let view = &buffer_views[draco.buffer_view as usize];
+            let data = buffer_range(buffers, view.buffer, view.byte_offset, view.byte_length)
+                .ok_or_else(|| invalid("Compressed data is out of range of its buffer.".to_string()))?;
+
+            let decoded = super::draco::decode(data).map_err(decoder_error(&path))?;
+
+            let mut data = Vec::new();
+            let mut views = Vec::new();
+
+            if let Some(indices) = primitive.indices {
+                let accessor = indices as usize;
+
+                let start = data.len();
+                for &index in &decoded.indices {
+                    if !write_component(&mut data, &accessors[accessor].component_type, false, index as f64) {
+                        return Err(invalid(format!("Index {index} doesn't fit the component type of accessors[{accessor}].")));
+                    }
+                }
+
+                views.push((accessor, start, data.len(), decoded.indices.len()));
+            }
+
+            for (semantic, &unique_id) in &draco.attributes {
+                let Some(&accessor) = primitive.attributes.get(semantic) else {
+                    continue;
+                };
+
+                let accessor = accessor as usize;
+
+                let attribute = decoded.attributes.iter().find(|a| a.unique_id as i64 == unique_id as i64)
+                    .ok_or_else(|| invalid(format!("The compressed data has no attribute {unique_id}.")))?;
+
+                let target = &accessors[accessor];
+                if attribute.num_components != target.accessor_type.num_components() {
+                    return Err(invalid(format!("Attribute {unique_id} does not match the type of its accessor.")));
+                }
+
+                // Components have to stay aligned to their size.
+                while data.len() % 4 != 0 {
+                    data.push(0);
+                }
+
+                let start = data.len();
+                let fits = match &attribute.values {
+                    AttributeValues::Float(values) => values.iter().all(|&value| write_component(&mut data, &target.component_type, target.normalized, value as f64)),
+                    AttributeValues::Int(values) => values.iter().all(|&value| write_component(&mut data, &target.component_type, false, value as f64))
+                };
+
+                if !fits {
+                    return Err(invalid(format!("Attribute {unique_id} has values that don't fit the component type of accessors[{accessor}].")));
+                }
+
+                views.push((accessor, start, data.len(), decoded.num_points));
+            }
+
+            let buffer = buffers.len() as i32;
+            for (accessor, start, end, count) in views {
+                accessors[accessor].buffer_view = Some(buffer_views.len() as i32);
+                accessors[accessor].byte_offset = 0;
+                accessors[accessor].count = count as i32;
+
+                buffer_views.push(BufferView {
+                    buffer,
+                    byte_offset: start as i32,
+                    byte_length: (end - start) as i32,
+                    byte_stride: None,
+                    target: None,
+                    name: None,
+                    meshopt: None
+                });
+            }
+
+            buffers.push(Buffer {
+                data
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a single component in the accessor's format. Floats written to normalized integers are scaled to the full range.
+/// Writes `value` as a component of `component_type`. Returns `false` if the component type can't hold it.
+fn write_component(data: &mut Vec<u8>, component_type: &ComponentType, normalized: bool, value: f64) -> bool {
+    let (min, max) = match component_type {
+        ComponentType::Byte => (i8::MIN as f64, i8::MAX as f64),
+        ComponentType::UnsignedByte => (0.0, u8::MAX as f64),
+        ComponentType::Short => (i16::MIN as f64, i16::MAX as f64),
+        ComponentType::UnsignedShort => (0.0, u16::MAX as f64),
+        ComponentType::UnsignedInt => (0.0, u32::MAX as f64),
+        ComponentType::Float => {
+            data.extend_from_slice(&(value as f32).to_le_bytes());
+            return true;
+        }
+    };
+
+    let value = if normalized { (value * max).round() } else { value };
+    if !(min..=max).contains(&value) || value.fract() != 0.0 {
+        return false;
+    }
+
+    match component_type {
+        ComponentType::Byte => data.push(value as i8 as u8),
+        ComponentType::UnsignedByte => data.push(value as u8),
+        ComponentType::Short => data.extend_from_slice(&(value as i16).to_le_bytes()),
+        ComponentType::UnsignedShort => data.extend_from_slice(&(value as u16).to_le_bytes()),
+        ComponentType::UnsignedInt => data.extend_from_slice(&(value as u32).to_le_bytes()),
+        // Floats were written above.
+        ComponentType::Float => {}
+    }
+
+    true
+}
 
-    let scalar = if let Some(scale) = value.get("scale") { 
-        scale.as_f64().unwrap() as f32
-    } else if let Some(strength) = value.get("strength") {
-        strength.as_f64().unwrap() as f32
-    } else {
-        1.0
+fn get_texture_info(value: &Json, num_textures: usize) -> Result<TextureInfo, ImportError> {
+    let index = value.field("index")?.index(num_textures)?;
+    let tex_coord = value.get("texCoord").map(|tc| tc.non_negative()).transpose()?.unwrap_or(0);
+
+    // Normal textures have a scale, and occlusion textures have a strength.
+    let scalar = match value.get_f32("scale")? {
+        Some(scale) => scale,
+        None => value.get_f32("strength")?.unwrap_or(1.0)
     };
 
-    TextureInfo { index, tex_coord, scalar }
+    let transform = value.get("extensions").and_then(|ex| ex.get("KHR_texture_transform")).map(|tt| -> Result<_, ImportError> {
+        let offset = tt.get("offset").map(|of| of.vec2()).transpose()?.unwrap_or(crate::Vec2 { x: 0.0, y: 0.0 });
+
+        let rotation = tt.get_f32("rotation")?.unwrap_or(0.0);
+
+        let scale = tt.get("scale").map(|sc| sc.vec2()).transpose()?.unwrap_or(crate::Vec2 { x: 1.0, y: 1.0 });
+
+        let tex_coord = tt.get("texCoord").map(|tc| tc.non_negative()).transpose()?;
+
+        Ok(TextureTransform { offset, rotation, scale, tex_coord })
+    }).transpose()?;
+
+    Ok(TextureInfo { index, tex_coord, scalar, transform })
 }
 
-fn get_texture_filter(value: i64) -> TextureFilter {
-    match value {
-        9728 => TextureFilter::Nearest,
-        9729 => TextureFilter::Linear,
-        9984 => TextureFilter::NearestMipmapNearest,
-        9985 => TextureFilter::LinearMipmapNearest,
-        9986 => TextureFilter::NearestMipmapLinear,
-        9987 => TextureFilter::LinearMipmapLinear,
-        tf => panic!("Invalid texture filter {tf}")
+fn get_texture_filter(value: &Json) -> Result<TextureFilter, ImportError> {
+    match value.i32()? {
+        9728 => Ok(TextureFilter::Nearest),
+        9729 => Ok(TextureFilter::Linear),
+        9984 => Ok(TextureFilter::NearestMipmapNearest),
+        9985 => Ok(TextureFilter::LinearMipmapNearest),
+        9986 => Ok(TextureFilter::NearestMipmapLinear),
+        9987 => Ok(TextureFilter::LinearMipmapLinear),
+        tf => Err(value.invalid(format!("Invalid texture filter {tf}.")))
     }
 }
 
-fn get_wrap_mode(value: i64) -> TextureWrapMode {
-    match value {
-        33071 => TextureWrapMode::ClampToEdge,
-        33648 => TextureWrapMode::MirroredRepeat,
-        10497 => TextureWrapMode::Repeat,
-        wm => panic!("Invalid wrap mode {wm}.")
+fn get_wrap_mode(value: &Json) -> Result<TextureWrapMode, ImportError> {
+    match value.i32()? {
+        33071 => Ok(TextureWrapMode::ClampToEdge),
+        33648 => Ok(TextureWrapMode::MirroredRepeat),
+        10497 => Ok(TextureWrapMode::Repeat),
+        wm => Err(value.invalid(format!("Invalid wrap mode {wm}.")))
     }
-}
\ No newline at end of file
+}
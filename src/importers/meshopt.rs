@@ -0,0 +1,393 @@
+//! Decoders for the meshoptimizer codecs used by `EXT_meshopt_compression`.
+
+use std::io;
+
+const VERTEX_HEADER: u8 = 0xA0;
+const INDEX_HEADER: u8 = 0xE0;
+const SEQUENCE_HEADER: u8 = 0xD0;
+
+const VERTEX_BLOCK_SIZE_BYTES: usize = 8192;
+const VERTEX_BLOCK_MAX_SIZE: usize = 256;
+const BYTE_GROUP_SIZE: usize = 16;
+const BYTE_GROUP_DECODE_LIMIT: usize = 24;
+const TAIL_MIN_SIZE: usize = 32;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn truncated() -> io::Error {
+    invalid("Meshopt compressed data is truncated.")
+}
+
+/// Decodes a vertex buffer of `count` elements, each `stride` bytes.
+pub fn decode_vertex_buffer(data: &[u8], count: usize, stride: usize) -> Result<Vec<u8>, io::Error> {
+    if stride == 0 || stride > 256 || !stride.is_multiple_of(4) {
+        return Err(invalid("Meshopt vertex data must have a stride that is a multiple of 4, up to 256."));
+    }
+
+    if data.len() < 1 + stride {
+        return Err(truncated());
+    }
+
+    if data[0] & 0xF0 != VERTEX_HEADER || data[0] & 0x0F > 0 {
+        return Err(invalid("Unrecognized meshopt vertex data version."));
+    }
+
+    // The tail holds the first vertex, which the deltas of the first block are relative to.
+    let tail_size = stride.max(TAIL_MIN_SIZE);
+    if data.len() < 1 + tail_size {
+        return Err(truncated());
+    }
+
+    // Every 64 vertices need at least one header byte per byte of the vertex.
+    if count.div_ceil(64) * stride > data.len() {
+        return Err(truncated());
+    }
+
+    let mut last_vertex = data[data.len() - stride..].to_vec();
+
+    let block_size = ((VERTEX_BLOCK_SIZE_BYTES / stride) & !(BYTE_GROUP_SIZE - 1)).min(VERTEX_BLOCK_MAX_SIZE);
+    let mut output = vec![0; count.checked_mul(stride).ok_or_else(truncated)?];
+    let mut bytes = [0; VERTEX_BLOCK_MAX_SIZE];
+    let mut position = 1;
+
+    // Vertices are stored in blocks, with each byte of the vertex delta coded separately.
+    for vertices in output.chunks_mut(block_size * stride) {
+        let num_vertices = vertices.len() / stride;
+        let aligned = num_vertices.next_multiple_of(BYTE_GROUP_SIZE);
+
+        for (k, last) in last_vertex.iter_mut().enumerate() {
+            position = decode_bytes(data, position, &mut bytes[..aligned])?;
+
+            let mut previous = *last;
+            for (i, &byte) in bytes[..num_vertices].iter().enumerate() {
+                let delta = (byte >> 1) ^ (byte & 1).wrapping_neg();
+                previous = previous.wrapping_add(delta);
+                vertices[i * stride + k] = previous;
+            }
+
+            *last = previous;
+        }
+    }
+
+    if position != data.len() - tail_size {
+        return Err(invalid("Meshopt vertex data has trailing bytes."));
+    }
+
+    Ok(output)
+}
+
+/// Decodes the byte groups of a single byte of every vertex in a block, returning the new position in `data`.
+fn decode_bytes(data: &[u8], mut position: usize, output: &mut [u8]) -> Result<usize, io::Error> {
+    let header_size = (output.len() / BYTE_GROUP_SIZE).div_ceil(4);
+    let header = data.get(position..position + header_size).ok_or_else(truncated)?;
+    position += header_size;
+
+    for (i, group) in output.chunks_exact_mut(BYTE_GROUP_SIZE).enumerate() {
+        // Encoders always leave enough data after a group, since the reference decoder reads ahead.
+        if data.len().saturating_sub(position) < BYTE_GROUP_DECODE_LIMIT {
+            return Err(truncated());
+        }
+
+        let bits_log2 = (header[i / 4] >> ((i % 4) * 2)) & 3;
+        position = match bits_log2 {
+            0 => {
+                group.fill(0);
+                position
+            },
+            3 => {
+                group.copy_from_slice(data.get(position..position + BYTE_GROUP_SIZE).ok_or_else(truncated)?);
+                position + BYTE_GROUP_SIZE
+            },
+            _ => {
+                let bits = 1 << bits_log2;
+                let packed = BYTE_GROUP_SIZE * bits / 8;
+                let codes = data.get(position..position + packed).ok_or_else(truncated)?;
+                let mut literal = position + packed;
+                let escape = (1u8 << bits) - 1;
+
+                for (j, value) in group.iter_mut().enumerate() {
+                    let shift = 8 - bits - (j * bits) % 8;
+                    let code = (codes[j * bits / 8] >> shift) & escape;
+                    *value = if code == escape {
+                        literal += 1;
+                        *data.get(literal - 1).ok_or_else(truncated)?
+                    } else {
+                        code
+                    };
+                }
+
+                literal
+            }
+        };
+    }
+
+    Ok(position)
+}
+
+fn read_vbyte(data: &[u8], position: &mut usize) -> Result<u32, io::Error> {
+    let mut result = 0;
+    for i in 0..5 {
+        let byte = *data.get(*position).ok_or_else(truncated)?;
+        *position += 1;
+
+        result |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte < 0x80 {
+            break;
+        }
+    }
+
+    Ok(result)
+}
+
+fn read_index(data: &[u8], position: &mut usize, last: u32) -> Result<u32, io::Error> {
+    let value = read_vbyte(data, position)?;
+    Ok(last.wrapping_add((value >> 1) ^ (value & 1).wrapping_neg()))
+}
+
+/// Decodes a triangle list of `count` indices.
+pub fn decode_index_buffer(data: &[u8], count: usize) -> Result<Vec<u32>, io::Error> {
+    if !count.is_multiple_of(3) {
+        return Err(invalid("Meshopt triangle data must have a multiple of 3 indices."));
+    }
+
+    // Every triangle takes at least one byte, and the code table is stored at the end.
+    if data.len() < 1 + count / 3 + 16 {
+        return Err(truncated());
+    }
+
+    let version = data[0] & 0x0F;
+    if data[0] & 0xF0 != INDEX_HEADER || version > 1 {
+        return Err(invalid("Unrecognized meshopt index data version."));
+    }
+
+    let data_end = data.len() - 16;
+    let code_aux = &data[data_end..];
+    let codes = &data[1..1 + count / 3];
+    let mut position = 1 + count / 3;
+
+    let mut edges = [[u32::MAX; 2]; 16];
+    let mut vertices = [u32::MAX; 16];
+    let (mut edge_offset, mut vertex_offset) = (0usize, 0usize);
+    let (mut next, mut last) = (0u32, 0u32);
+    let fec_max = if version >= 1 { 13 } else { 15 };
+
+    let mut indices = Vec::with_capacity(count);
+    for &code in codes {
+        if position > data_end {
+            return Err(truncated());
+        }
+
+        let vertex = |offset: usize, fe: u8| vertices[offset.wrapping_sub(fe as usize) & 15];
+
+        let (a, b, c) = if code < 0xF0 {
+            // The triangle shares an edge with a recent one.
+            let [a, b] = edges[edge_offset.wrapping_sub(1 + (code >> 4) as usize) & 15];
+            let fec = code & 15;
+
+            let c = if fec < fec_max {
+                if fec == 0 {
+                    next += 1;
+                    next - 1
+                } else {
+                    vertex(vertex_offset, fec + 1)
+                }
+            } else {
+                // Versions after 0 use 13 and 14 for the last free index, plus or minus one.
+                last = if fec != 15 { last.wrapping_add(fec as u32).wrapping_sub((fec ^ 3) as u32) } else { read_index(data, &mut position, last)? };
+                last
+            };
+
+            if fec == 0 || fec >= fec_max {
+                vertices[vertex_offset] = c;
+                vertex_offset = (vertex_offset + 1) & 15;
+            }
+
+            (a, b, c)
+        } else {
+            let (fea, feb, fec) = if code < 0xFE {
+                let aux = code_aux[(code & 15) as usize];
+                (0, aux >> 4, aux & 15)
+            } else {
+                let aux = *data.get(position).ok_or_else(truncated)?;
+                position += 1;
+
+                // A zero code restarts the vertex numbering.
+                if aux == 0 {
+                    next = 0;
+                }
+
+                (if code == 0xFE { 0 } else { 15 }, aux >> 4, aux & 15)
+            };
+
+            let mut new_vertex = || {
+                next += 1;
+                next - 1
+            };
+
+            let mut a = if fea == 0 { new_vertex() } else { 0 };
+            let mut b = if feb == 0 { new_vertex() } else { vertex(vertex_offset, feb) };
+            let mut c = if fec == 0 { new_vertex() } else { vertex(vertex_offset, fec) };
+
+            if fea == 15 {
+                last = read_index(data, &mut position, last)?;
+                a = last;
+            }
+
+            if feb == 15 {
+                last = read_index(data, &mut position, last)?;
+                b = last;
+            }
+
+            if fec == 15 {
+                last = read_index(data, &mut position, last)?;
+                c = last;
+            }
+
+            vertices[vertex_offset] = a;
+            vertex_offset = (vertex_offset + 1) & 15;
+            for (vertex, fe) in [(b, feb), (c, fec)] {
+                vertices[vertex_offset] = vertex;
+                if fe == 0 || fe == 15 {
+                    vertex_offset = (vertex_offset + 1) & 15;
+                }
+            }
+
+            edges[edge_offset] = [b, a];
+            edge_offset = (edge_offset + 1) & 15;
+
+            (a, b, c)
+        };
+
+        edges[edge_offset] = [c, b];
+        edges[(edge_offset + 1) & 15] = [a, c];
+        edge_offset = (edge_offset + 2) & 15;
+
+        indices.extend_from_slice(&[a, b, c]);
+    }
+
+    if position != data_end {
+        return Err(invalid("Meshopt index data has trailing bytes."));
+    }
+
+    Ok(indices)
+}
+
+/// Decodes a sequence of `count` indices, such as a line list or a list of points.
+pub fn decode_index_sequence(data: &[u8], count: usize) -> Result<Vec<u32>, io::Error> {
+    // Every index takes at least one byte, followed by a four byte tail.
+    if data.len() < 1 + count + 4 {
+        return Err(truncated());
+    }
+
+    if data[0] & 0xF0 != SEQUENCE_HEADER || data[0] & 0x0F > 1 {
+        return Err(invalid("Unrecognized meshopt index sequence version."));
+    }
+
+    let data_end = data.len() - 4;
+    let mut position = 1;
+    let mut last = [0u32; 2];
+
+    let mut indices = Vec::with_capacity(count);
+    for _ in 0..count {
+        if position >= data_end {
+            return Err(truncated());
+        }
+
+        // The lowest bit picks which of the two previous indices this one is relative to.
+        let value = read_vbyte(data, &mut position)?;
+        let baseline = (value & 1) as usize;
+        let delta = value >> 1;
+
+        last[baseline] = last[baseline].wrapping_add((delta >> 1) ^ (delta & 1).wrapping_neg());
+        indices.push(last[baseline]);
+    }
+
+    if position != data_end {
+        return Err(invalid("Meshopt index sequence has trailing bytes."));
+    }
+
+    Ok(indices)
+}
+
+/// Reconstructs unit vectors stored as octahedral coordinates, with 8 or 16 bit components.
+pub fn filter_octahedral(data: &mut [u8], stride: usize) -> Result<(), io::Error> {
+    match stride {
+        4 => {
+            for element in data.chunks_exact_mut(4) {
+                let decoded = octahedral([element[0] as i8 as f32, element[1] as i8 as f32, element[2] as i8 as f32], i8::MAX as f32);
+                for (byte, value) in element.iter_mut().zip(decoded) {
+                    *byte = value as i8 as u8;
+                }
+            }
+        },
+        8 => {
+            for element in data.chunks_exact_mut(8) {
+                let component = |i: usize| i16::from_le_bytes([element[i * 2], element[i * 2 + 1]]) as f32;
+                let decoded = octahedral([component(0), component(1), component(2)], i16::MAX as f32);
+                for (i, value) in decoded.into_iter().enumerate() {
+                    element[i * 2..i * 2 + 2].copy_from_slice(&(value as i16).to_le_bytes());
+                }
+            }
+        },
+        _ => return Err(invalid("The meshopt octahedral filter requires a stride of 4 or 8."))
+    }
+
+    Ok(())
+}
+
+fn octahedral([x, y, z]: [f32; 3], max: f32) -> [i32; 3] {
+    let z = z - x.abs() - y.abs();
+
+    // Fold the coordinates back for the lower hemisphere.
+    let t = z.min(0.0);
+    let x = if x >= 0.0 { x + t } else { x - t };
+    let y = if y >= 0.0 { y + t } else { y - t };
+
+    let scale = max / (x * x + y * y + z * z).sqrt();
+    [x, y, z].map(|v| (v * scale).round() as i32)
+}
+
+/// Reconstructs unit quaternions from their three smallest components, stored as 16 bit integers.
+pub fn filter_quaternion(data: &mut [u8], stride: usize) -> Result<(), io::Error> {
+    if stride != 8 {
+        return Err(invalid("The meshopt quaternion filter requires a stride of 8."));
+    }
+
+    for element in data.chunks_exact_mut(8) {
+        let component = |i: usize| i16::from_le_bytes([element[i * 2], element[i * 2 + 1]]);
+
+        // The last component holds the scale in its high bits, and the index of the largest component in its low bits.
+        let last = component(3);
+        let scale = std::f32::consts::FRAC_1_SQRT_2 / (last | 3) as f32;
+        let [x, y, z] = [0, 1, 2].map(|i| component(i) as f32 * scale);
+        let w = (1.0 - x * x - y * y - z * z).max(0.0).sqrt();
+
+        let largest = (last & 3) as usize;
+        for (i, value) in [w, x, y, z].into_iter().enumerate() {
+            let offset = ((largest + i) & 3) * 2;
+            element[offset..offset + 2].copy_from_slice(&((value * i16::MAX as f32).round() as i16).to_le_bytes());
+        }
+    }
+
+    Ok(())
+}
+
+/// Reconstructs floats stored as a 24 bit mantissa and an 8 bit exponent.
+pub fn filter_exponential(data: &mut [u8], stride: usize) -> Result<(), io::Error> {
+    if !stride.is_multiple_of(4) {
+        return Err(invalid("The meshopt exponential filter requires a stride that is a multiple of 4."));
+    }
+
+    for value in data.chunks_exact_mut(4) {
+        let bits = u32::from_le_bytes([value[0], value[1], value[2], value[3]]);
+        let mantissa = ((bits << 8) as i32) >> 8;
+        let exponent = (bits as i32) >> 24;
+
+        let decoded = f32::from_bits(((exponent + 127) as u32) << 23) * mantissa as f32;
+        value.copy_from_slice(&decoded.to_le_bytes());
+    }
+
+    Ok(())
+}
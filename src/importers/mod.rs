@@ -1,8 +1,12 @@
-use std::io;
-
+mod draco;
+mod error;
 pub mod gltf;
+mod meshopt;
+mod resolver;
+
+pub use error::ImportError;
+pub use resolver::{percent_decode, FileResolver, ResourceResolver};
 
 pub trait Importer {
-    // TODO: Custom importer error.
-    fn import(data: &str) -> Result<Self, io::Error> where Self: Sized;
-}
\ No newline at end of file
+    fn import(data: &str) -> Result<Self, ImportError> where Self: Sized;
+}
@@ -0,0 +1,49 @@
+use std::{io, path::Path};
+
+/// Loads the external resources a document references, such as buffers.
+pub trait ResourceResolver {
+    /// Returns the contents of `uri`, relative to `base`. The URI has already been percent-decoded.
+    ///
+    /// `base` is the directory of the document, or empty if the document was loaded from memory.
+    fn resolve(&mut self, base: &Path, uri: &str) -> Result<Vec<u8>, io::Error>;
+}
+
+/// Loads resources from the filesystem, relative to the document.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileResolver;
+
+impl ResourceResolver for FileResolver {
+    fn resolve(&mut self, base: &Path, uri: &str) -> Result<Vec<u8>, io::Error> {
+        std::fs::read(base.join(uri))
+    }
+}
+
+/// Closures are given the URI only, which is enough for documents loaded from memory.
+impl<F: FnMut(&str) -> Result<Vec<u8>, io::Error>> ResourceResolver for F {
+    fn resolve(&mut self, _: &Path, uri: &str) -> Result<Vec<u8>, io::Error> {
+        self(uri)
+    }
+}
+
+/// Decodes the `%XX` escapes in a URI. Malformed escapes are kept as they are.
+pub fn percent_decode(uri: &str) -> Vec<u8> {
+    let bytes = uri.as_bytes();
+    let hex = |byte: u8| (byte as char).to_digit(16).map(|digit| digit as u8);
+
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if let (b'%', Some(&high), Some(&low)) = (bytes[i], bytes.get(i + 1), bytes.get(i + 2)) {
+            if let (Some(high), Some(low)) = (hex(high), hex(low)) {
+                decoded.push((high << 4) | low);
+                i += 3;
+                continue;
+            }
+        }
+
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    decoded
+}
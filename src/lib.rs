@@ -1,12 +1,14 @@
-use std::{io, fs, path::Path};
+use std::io::{Read, Seek};
 
-use importers::Importer;
-
-use crate::binary_reader::BinaryReader;
+use importers::{ImportError, Importer, ResourceResolver};
 
 pub mod importers;
-mod binary_reader;
 mod impassec;
+mod corners;
+mod normals;
+mod tangents;
+#[cfg(feature = "images")]
+mod images;
 //mod utils;
 
 #[repr(C)]
@@ -20,11 +22,23 @@ pub enum TextureType {
     Emissive
 }
 
+/// A UV transform, applied as `translation * rotation * scale` to the texture coordinates.
+#[repr(C)]
+#[derive(Debug)]
+pub struct TextureTransform {
+    pub offset:   Vec2,
+    pub rotation: f32,
+    pub scale:    Vec2
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct TextureIndex {
-    pub index:  usize,
-    pub t_type: TextureType
+    pub index:     usize,
+    pub t_type:    TextureType,
+    /// The texture coordinate set to sample with: 0 is `tex_coord` and 1 is `tex_coord_1`. Other sets aren't imported.
+    pub tex_coord: usize,
+    pub transform: TextureTransform
 }
 
 #[repr(C)]
@@ -35,11 +49,84 @@ pub enum AlphaMode {
     Blend
 }
 
+/// A morph target. Each displacement is stored per vertex, and is empty if the
+/// blend shape does not affect that attribute.
+#[derive(Debug)]
+pub struct BlendShape {
+    pub name:      Option<String>,
+    pub weight:    f32,
+    pub positions: Vec<Vec3>,
+    pub normals:   Vec<Vec3>,
+    pub tangents:  Vec<Vec3>
+}
+
+/// How a mesh's indices are drawn. Strips, loops and fans are converted to lists when they are imported.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimitiveType {
+    Points,
+    Lines,
+    Triangles
+}
+
 #[derive(Debug)]
 pub struct Mesh {
-    pub vertices: Vec<VertexPositionColorTextureNormalTangentBitangent>,
-    pub indices:  Vec<u32>,
-    pub material: usize
+    pub vertices:       Vec<VertexPositionColorTextureNormalTangentBitangent>,
+    pub indices:        Vec<u32>,
+    pub primitive_type: PrimitiveType,
+    pub material:       usize,
+    pub blend_shapes: Vec<BlendShape>
+}
+
+impl Mesh {
+    /// Replaces the normals of a triangle mesh. Vertices are split where their triangles need different normals.
+    /// Tangents are generated from normals, so call [`Mesh::generate_tangents`] afterwards if the mesh has them.
+    pub fn generate_normals(&mut self, mode: NormalGeneration) {
+        if self.primitive_type == PrimitiveType::Triangles {
+            let copied = normals::generate(&mut self.vertices, &mut self.indices, mode);
+            self.copy_blend_shapes(&copied);
+        }
+    }
+
+    /// Replaces the tangents and bitangents of a triangle mesh with MikkTSpace tangents, generated from the normals
+    /// and the first texture coordinate set. Vertices are split where their triangles need different tangents.
+    pub fn generate_tangents(&mut self) {
+        if self.primitive_type == PrimitiveType::Triangles {
+            let copied = tangents::generate(&mut self.vertices, &mut self.indices);
+            self.copy_blend_shapes(&copied);
+        }
+    }
+
+    /// Gives copied vertices the same displacements as the vertices they were copied from.
+    fn copy_blend_shapes(&mut self, copied: &[u32]) {
+        for shape in &mut self.blend_shapes {
+            for displacements in [&mut shape.positions, &mut shape.normals, &mut shape.tangents] {
+                if !displacements.is_empty() {
+                    for &vertex in copied {
+                        displacements.push(displacements[vertex as usize]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How normals are generated for triangles that don't have any.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NormalGeneration {
+    /// Every triangle is flat shaded, as the glTF spec requires.
+    #[default]
+    Flat,
+    /// Normals are averaged over the triangles around each vertex, weighted by area and angle. Edges where the
+    /// triangles meet at more than `crease_angle` radians stay hard.
+    Smooth { crease_angle: f32 }
+}
+
+/// Options for turning a glTF document into a [`Scene`].
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// How normals are generated for primitives without a `NORMAL` attribute.
+    pub normals: NormalGeneration
 }
 
 #[derive(Debug)]
@@ -54,147 +141,496 @@ pub struct Material {
     pub textures:         Vec<TextureIndex>
 }
 
+/// The material glTF uses for primitives without one.
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            albedo_color: Vec4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 },
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            emissive_factor: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+            textures: Vec::new()
+        }
+    }
+}
+
+/// A texture's image. External images have a path relative to the scene, embedded images have their encoded data.
 #[derive(Debug)]
 pub struct Texture {
-    pub path:   Option<String>,
-    pub data:   Option<Vec<u8>>
+    pub path:      Option<String>,
+    pub data:      Option<Vec<u8>>,
+    /// The MIME type of the data, such as "image/png", if it is known.
+    pub mime_type: Option<String>,
+    /// The decoded image, once [`Scene::decode_textures`] has been called.
+    pub pixels:    Option<Pixels>,
+    pub sampler:   Sampler
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    ClampToEdge,
+    MirroredRepeat,
+    Repeat
+}
+
+/// How a texture is filtered and wrapped. A texture without a sampler uses the default, which repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sampler {
+    /// `None` if the renderer should pick the filter.
+    pub mag_filter: Option<TextureFilter>,
+    /// `None` if the renderer should pick the filter.
+    pub min_filter: Option<TextureFilter>,
+    pub wrap_s:     WrapMode,
+    pub wrap_t:     WrapMode
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler { mag_filter: None, min_filter: None, wrap_s: WrapMode::Repeat, wrap_t: WrapMode::Repeat }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    Srgb,
+    Linear
+}
+
+/// Decoded pixels, stored row by row from the top left.
+#[derive(Debug)]
+pub struct Pixels {
+    pub width:       u32,
+    pub height:      u32,
+    /// Images are always expanded to RGBA, so this is 4.
+    pub channels:    u8,
+    /// 8 or 16. 16 bit channels are stored little endian.
+    pub bit_depth:   u8,
+    /// Color textures are sRGB, textures holding data such as normals are linear.
+    pub color_space: ColorSpace,
+    pub data:        Vec<u8>
+}
+
+#[derive(Debug)]
+pub enum Projection {
+    Perspective {
+        fov_y:        f32,
+        aspect_ratio: Option<f32>,
+        near:         f32,
+        /// `None` if the camera uses an infinite far plane.
+        far:          Option<f32>
+    },
+
+    Orthographic {
+        x_mag: f32,
+        y_mag: f32,
+        near:  f32,
+        far:   f32
+    }
+}
+
+#[derive(Debug)]
+pub struct Camera {
+    pub name:       Option<String>,
+    /// The index of the node this camera is attached to.
+    pub node:       usize,
+    pub projection: Projection
+}
+
+#[derive(Debug)]
+pub enum LightType {
+    Directional,
+    Point,
+    Spot {
+        inner_cone_angle: f32,
+        outer_cone_angle: f32
+    }
+}
+
+#[derive(Debug)]
+pub struct Light {
+    pub name:       Option<String>,
+    /// The index of the node this light is attached to.
+    pub node:       usize,
+    pub light_type: LightType,
+    pub color:      Vec3,
+    pub intensity:  f32,
+    /// `None` if the light has infinite range.
+    pub range:      Option<f32>
 }
 
 #[derive(Debug)]
 pub struct Scene {
     pub meshes:    Vec<Mesh>,
     pub materials: Vec<Material>,
-    pub textures: Vec<Texture>
+    pub textures: Vec<Texture>,
+    pub cameras:   Vec<Camera>,
+    pub lights:    Vec<Light>,
+    pub nodes:     Vec<Node>,
+    /// Every scene in the file. If the file has none, a single scene holds every node without a parent.
+    pub scenes:    Vec<SceneRoots>,
+    /// The scene [`Scene::root_nodes`] uses. Defaults to the file's `scene`, or the first scene.
+    /// Change it to show a different scene.
+    pub active_scene: Option<usize>
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub name:      Option<String>,
+    /// The transform relative to the parent node.
+    pub transform: Mat4,
+    /// The indices into [`Scene::meshes`]. A glTF mesh is split into one mesh per material.
+    pub meshes:    Vec<usize>,
+    /// The index into [`Scene::cameras`].
+    pub camera:    Option<usize>,
+    /// The index into [`Scene::lights`].
+    pub light:     Option<usize>,
+    pub parent:    Option<usize>,
+    pub children:  Vec<usize>
+}
+
+#[derive(Debug)]
+pub struct SceneRoots {
+    pub name:  Option<String>,
+    /// The indices of the root nodes.
+    pub nodes: Vec<usize>
 }
 
 impl Scene {
-    pub fn from_gltf(path: &str) -> Result<Scene, io::Error> {
-        let gltf = importers::gltf::Gltf::import(path)?;
+    /// Returns the root nodes of the active scene.
+    pub fn root_nodes(&self) -> &[usize] {
+        self.active_scene.and_then(|s| self.scenes.get(s)).map_or(&[], |scene| &scene.nodes)
+    }
 
-        if gltf.buffers.is_none() || gltf.accessors.is_none() {
-            return Err(io::Error::new(io::ErrorKind::InvalidInput, "glTF does not contain enough information to load anything useful."));
+    /// Returns the transform of `node` relative to the scene root.
+    pub fn world_matrix(&self, node: usize) -> Mat4 {
+        let mut matrix = self.nodes[node].transform;
+        let mut parent = self.nodes[node].parent;
+        while let Some(p) = parent {
+            matrix = self.nodes[p].transform * matrix;
+            parent = self.nodes[p].parent;
         }
 
-        let buffers = gltf.buffers.as_ref().unwrap();
-        let accessors = gltf.accessors.as_ref().unwrap();
-        let buffer_views = gltf.buffer_views.unwrap();
+        matrix
+    }
+
+    /// Returns the transform of every node relative to the scene root, in the same order as [`Scene::nodes`].
+    pub fn world_matrices(&self) -> Vec<Mat4> {
+        let mut matrices: Vec<Option<Mat4>> = vec![None; self.nodes.len()];
+
+        // Parents are computed first, so every node only multiplies by its parent's world matrix.
+        let mut stack: Vec<usize> = (0..self.nodes.len()).filter(|&n| self.nodes[n].parent.is_none()).collect();
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            matrices[n] = Some(node.parent.and_then(|p| matrices[p]).map_or(node.transform, |parent| parent * node.transform));
+            stack.extend_from_slice(&node.children);
+        }
+
+        // Nodes are checked for cycles when they are imported, so every node has been reached.
+        matrices.into_iter().map(|m| m.unwrap_or(Mat4::IDENTITY)).collect()
+    }
+
+    pub fn from_gltf(path: &str) -> Result<Scene, ImportError> {
+        Scene::from_gltf_document(importers::gltf::Gltf::import(path)?, &ImportOptions::default())
+    }
+
+    /// Loads a glTF or GLB file and its external buffers with `resolver`, see [`importers::gltf::Gltf::import_with`].
+    pub fn from_gltf_with(path: &str, resolver: &mut impl ResourceResolver) -> Result<Scene, ImportError> {
+        Scene::from_gltf_document(importers::gltf::Gltf::import_with(path, resolver)?, &ImportOptions::default())
+    }
+
+    /// Loads a glTF or GLB file from memory. External buffers are loaded with `resolver`, see [`importers::gltf::Gltf::from_bytes`].
+    pub fn from_bytes(data: &[u8], resolver: &mut impl ResourceResolver) -> Result<Scene, ImportError> {
+        Scene::from_gltf_document(importers::gltf::Gltf::from_bytes(data, resolver)?, &ImportOptions::default())
+    }
+
+    /// Loads a glTF or GLB file from the rest of `reader`. External buffers are loaded with `resolver`, see [`importers::gltf::Gltf::from_bytes`].
+    pub fn from_reader<R: Read + Seek>(reader: R, resolver: &mut impl ResourceResolver) -> Result<Scene, ImportError> {
+        Scene::from_gltf_document(importers::gltf::Gltf::from_reader(reader, resolver)?, &ImportOptions::default())
+    }
+
+    /// Decodes every PNG and JPEG texture to pixels. External images are loaded with `resolver`,
+    /// relative to `base`, which is usually the directory of the scene. Images in other formats are
+    /// left undecoded.
+    #[cfg(feature = "images")]
+    pub fn decode_textures(&mut self, base: &std::path::Path, resolver: &mut impl ResourceResolver) -> Result<(), ImportError> {
+        for (t, texture) in self.textures.iter_mut().enumerate() {
+            // glTF only stores colors in sRGB, everything else is linear.
+            let is_color = self.materials.iter().flat_map(|m| &m.textures)
+                .any(|ti| ti.index == t && matches!(ti.t_type, TextureType::Albedo | TextureType::Emissive));
+            let color_space = if is_color { ColorSpace::Srgb } else { ColorSpace::Linear };
+
+            let external;
+            let data = match (&texture.data, &texture.path) {
+                (Some(data), _) => data,
+                (None, Some(path)) => {
+                    external = resolver.resolve(base, path)
+                        .map_err(|error| ImportError::Resource { path: format!("textures[{t}]"), error })?;
+                    &external
+                },
+                (None, None) => continue
+            };
+
+            texture.pixels = images::decode(data, &format!("textures[{t}]"), color_space)?;
+        }
+
+        Ok(())
+    }
+
+    /// Converts a loaded glTF document to a scene. The other constructors use the default options.
+    pub fn from_gltf_document(gltf: importers::gltf::Gltf, options: &ImportOptions) -> Result<Scene, ImportError> {
+        let (Some(_), Some(accessors)) = (&gltf.buffers, &gltf.accessors) else {
+            return Err(ImportError::InvalidData { path: String::new(), message: "glTF does not contain enough information to load anything useful.".to_string() });
+        };
 
         let mut meshes = Vec::new();
+        // The meshes each glTF mesh was split into.
+        let mut mesh_groups: Vec<Vec<usize>> = Vec::new();
+
+        // Primitives without a material use the default material, which goes after the file's materials.
+        let default_material = gltf.materials.as_ref().map_or(0, |m| m.len());
+        let mut uses_default_material = false;
 
-        for mesh in gltf.meshes.unwrap().iter() {
-            let mut vertices = Vec::new();
-            let mut indices = Vec::new();
+        for (m, mesh) in gltf.meshes.iter().flatten().enumerate() {
+            // Every primitive in a mesh must have the same number of morph targets.
+            let num_targets = mesh.primitives[0].targets.as_ref().map_or(0, |t| t.len());
 
-            let material = mesh.primitives[0].material;
-            if material.is_none() {
-                todo!("No material is defined!");
+            // Primitives are grouped by material and primitive type, and each group becomes its own mesh.
+            let mut groups: Vec<(usize, PrimitiveType, Vec<usize>)> = Vec::new();
+            for (p, primitive) in mesh.primitives.iter().enumerate() {
+                let material = primitive.material.map_or(default_material, |material| material as usize);
+                uses_default_material |= primitive.material.is_none();
+
+                let primitive_type = get_primitive_type(&primitive.mode);
+
+                match groups.iter_mut().find(|(mat, pt, _)| *mat == material && *pt == primitive_type) {
+                    Some((_, _, primitives)) => primitives.push(p),
+                    None => groups.push((material, primitive_type, vec![p]))
+                }
             }
 
-            for primitive in mesh.primitives.iter() {
-                if primitive.material != material {
-                    todo!("Material is different!")
+            let mut group_meshes = Vec::with_capacity(groups.len());
+            for (material, primitive_type, primitives) in groups {
+                let mut vertices = Vec::new();
+                let mut indices = Vec::new();
+
+                let mut blend_shapes = Vec::with_capacity(num_targets);
+                for i in 0..num_targets {
+                    blend_shapes.push(BlendShape {
+                        name: mesh.target_names.as_ref().and_then(|n| n.get(i)).filter(|n| !n.is_empty()).cloned(),
+                        weight: mesh.weights.as_ref().and_then(|w| w.get(i).copied()).unwrap_or(0.0),
+                        positions: Vec::new(),
+                        normals: Vec::new(),
+                        tangents: Vec::new()
+                    });
                 }
 
-                for (name, index) in &primitive.attributes {
-                    let accessor = &accessors[*index as usize];
-                    let view = &buffer_views[accessor.buffer_view.unwrap() as usize];
-                    let data = &buffers[view.buffer as usize].data[view.byte_offset as usize..view.byte_offset as usize + view.byte_length as usize];
-                    let reader = BinaryReader::new(data);
-
-                    let name = name.to_lowercase();
-                    let name = name.split('_').collect::<Vec<&str>>();
-
-                    if (accessor.count as usize) > vertices.len() {
-                        for _ in 0..(accessor.count as usize - vertices.len()) {
-                            vertices.push(VertexPositionColorTextureNormalTangentBitangent {
-                                position: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
-                                color: Vec4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
-                                tex_coord: Vec2 { x: 0.0, y: 0.0 },
-                                normal: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
-                                tangent: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
-                                bitangent: Vec3 { x: 0.0, y: 0.0, z: 0.0 }
-                            });
+                for &p in &primitives {
+                    let primitive = &mesh.primitives[p];
+                    let path = format!("meshes[{m}].primitives[{p}]");
+
+                    let prim_targets = primitive.targets.as_ref().map_or(0, |t| t.len());
+                    if prim_targets != num_targets {
+                        return Err(ImportError::InvalidData { path: format!("{path}.targets"), message: format!("Every primitive in a mesh must have the same number of morph targets, found {prim_targets} and {num_targets}.") });
+                    }
+
+                    // Primitives are appended one after another, so their indices are offset by the vertices before them.
+                    let base = vertices.len();
+
+                    // Every attribute has one element per vertex.
+                    let count = primitive.attributes.values().map(|&index| accessors[index as usize].count as usize).max().unwrap_or(0);
+                    for (attribute, &index) in &primitive.attributes {
+                        let attr_count = accessors[index as usize].count as usize;
+                        if attr_count != count {
+                            return Err(ImportError::InvalidData { path: format!("{path}.attributes.{attribute}"), message: format!("Attribute has {attr_count} elements, but the primitive has {count} vertices.") });
                         }
-                    } else if (accessor.count as usize) < vertices.len() {
-                        // TODO: Don't panic - these situations (if they can happen) should be handled.
-                        todo!("AAAAAAAAAAAAA IT'S NOT SUPPOSED TO BE LIKE THIS YET");
                     }
-                    
-                    match name[0].to_lowercase().as_str() {
-                        "position" => {
-                            let data = reinterpret_slice::<u8, f32>(data);
-                            let mut vertex = 0;
-                            for i in (0..data.len()).step_by(3) {
-                                vertices[vertex].position = Vec3 { x: data[i + 0], y: data[i + 1], z: data[i + 2] };
-                                vertex += 1;
+
+                    // Accessors without a buffer view, or ones that are never read, don't bound their count.
+                    vertices.try_reserve_exact(count)
+                        .map_err(|_| ImportError::InvalidData { path: format!("{path}.attributes"), message: format!("Not enough memory for {count} vertices.") })?;
+                    vertices.resize_with(base + count, || VertexPositionColorTextureNormalTangentBitangent {
+                        position: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+                        // Vertices without a color are white, so the color can always be multiplied in.
+                        color: Vec4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 },
+                        tex_coord: Vec2 { x: 0.0, y: 0.0 },
+                        tex_coord_1: Vec2 { x: 0.0, y: 0.0 },
+                        normal: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+                        tangent: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+                        bitangent: Vec3 { x: 0.0, y: 0.0, z: 0.0 }
+                    });
+                    let prim_vertices = &mut vertices[base..];
+                    let mut prim_tangents = None;
+
+                    for (attribute, &index) in &primitive.attributes {
+                        match attribute.as_str() {
+                            "POSITION" => {
+                                for (vertex, [x, y, z]) in gltf.read_accessor(index)?.into_iter().enumerate() {
+                                    prim_vertices[vertex].position = Vec3 { x, y, z };
+                                }
+                            }
+
+                            "NORMAL" => {
+                                for (vertex, [x, y, z]) in gltf.read_accessor(index)?.into_iter().enumerate() {
+                                    prim_vertices[vertex].normal = Vec3 { x, y, z };
+                                }
+                            }
+
+                            "TEXCOORD_0" => {
+                                for (vertex, [x, y]) in gltf.read_accessor(index)?.into_iter().enumerate() {
+                                    prim_vertices[vertex].tex_coord = Vec2 { x, y };
+                                }
                             }
-                        }
 
-                        "normal" => {
-                            let data = reinterpret_slice::<u8, f32>(data);
-                            let mut vertex = 0;
-                            for i in (0..data.len()).step_by(3) {
-                                vertices[vertex].normal = Vec3 { x: data[i + 0], y: data[i + 1], z: data[i + 2] };
-                                vertex += 1;
+                            "TEXCOORD_1" => {
+                                for (vertex, [x, y]) in gltf.read_accessor(index)?.into_iter().enumerate() {
+                                    prim_vertices[vertex].tex_coord_1 = Vec2 { x, y };
+                                }
                             }
+
+                            // Colors are RGB or RGBA, and integer colors are normalized.
+                            "COLOR_0" => if let importers::gltf::AccessorType::Vec3 = accessors[index as usize].accessor_type {
+                                for (vertex, [x, y, z]) in gltf.read_accessor(index)?.into_iter().enumerate() {
+                                    prim_vertices[vertex].color = Vec4 { x, y, z, w: 1.0 };
+                                }
+                            } else {
+                                for (vertex, [x, y, z, w]) in gltf.read_accessor(index)?.into_iter().enumerate() {
+                                    prim_vertices[vertex].color = Vec4 { x, y, z, w };
+                                }
+                            }
+
+                            // The bitangent needs the normal, which may not have been read yet.
+                            "TANGENT" => prim_tangents = Some(gltf.read_accessor::<f32, 4>(index)?),
+
+                            // Other texture coordinate and color sets, joints, weights and custom attributes.
+                            _ => {}
                         }
+                    }
 
-                        "texcoord" => {
-                            let data = reinterpret_slice::<u8, f32>(data);
-                            let mut vertex = 0;
-                            for i in (0..data.len()).step_by(2) {
-                                vertices[vertex].tex_coord = Vec2 { x: data[i + 0] as f32, y: data[i + 1] as f32 };
-                                vertex += 1;
+                    // Tangents are only meaningful with the normals they were made for, so they are ignored when normals
+                    // have to be generated.
+                    let has = |attribute: &str| primitive.attributes.contains_key(attribute);
+                    if has("NORMAL") {
+                        for (vertex, tangent) in prim_vertices.iter_mut().zip(prim_tangents.into_iter().flatten()) {
+                            tangents::set_tangent(vertex, tangent);
+                        }
+                    }
+                
+                    // Indices can be unsigned bytes, shorts or ints. Primitives without indices draw every vertex in order.
+                    let first_index = indices.len();
+                    let (indices_path, prim_indices) = match primitive.indices {
+                        Some(prim_indices) => {
+                            let prim_indices = gltf.read_accessor::<u32, 1>(prim_indices)?.into_iter().map(|[index]| index).collect::<Vec<u32>>();
+                            if let Some(index) = prim_indices.iter().find(|&&index| index as usize >= count) {
+                                return Err(ImportError::InvalidData { path: format!("{path}.indices"), message: format!("Index {index} is out of range for {count} vertices.") });
                             }
+
+                            (format!("{path}.indices"), prim_indices)
                         }
 
-                        //_ => return Err(io::Error::new(io::ErrorKind::Unsupported, format!("Unsupported attribute \"{}\"", name[0])))
-                        _ => {} // Ignore
+                        None => (path.clone(), (0..count as u32).collect())
+                    };
+
+                    let list_size = match primitive.mode {
+                        importers::gltf::Topology::Lines => 2,
+                        importers::gltf::Topology::Triangles => 3,
+                        _ => 1
+                    };
+                    if prim_indices.len() % list_size != 0 {
+                        return Err(ImportError::InvalidData { path: indices_path, message: format!("{} vertices can't be split into primitives of {list_size}.", prim_indices.len()) });
+                    }
+
+                    indices.extend(get_primitive_list(&primitive.mode, &prim_indices).into_iter().map(|index| base as u32 + index));
+
+                    // Tangents are generated from normals, so normals are generated first.
+                    let mut copied = Vec::new();
+                    if primitive_type == PrimitiveType::Triangles {
+                        if !has("NORMAL") {
+                            copied = normals::generate(&mut vertices, &mut indices[first_index..], options.normals);
+                        }
+
+                        if !(has("TANGENT") && has("NORMAL")) && has("TEXCOORD_0") {
+                            copied.extend(tangents::generate(&mut vertices, &mut indices[first_index..]));
+                        }
+                    }
+
+                    if let Some(targets) = &primitive.targets {
+                        for (t, (target, shape)) in targets.iter().zip(blend_shapes.iter_mut()).enumerate() {
+                            for (name, &index) in target {
+                                let displacements = match name.as_str() {
+                                    "POSITION" => &mut shape.positions,
+                                    "NORMAL" => &mut shape.normals,
+                                    "TANGENT" => &mut shape.tangents,
+                                    _ => continue
+                                };
+
+                                let data = gltf.read_accessor(index)?;
+                                if data.len() != count {
+                                    return Err(ImportError::InvalidData { path: format!("{path}.targets[{t}].{name}"), message: format!("Target has {} elements, but the primitive has {count} vertices.", data.len()) });
+                                }
+
+                                // Earlier primitives may not have displaced this attribute.
+                                displacements.resize(base, Vec3 { x: 0.0, y: 0.0, z: 0.0 });
+                                displacements.extend(data.into_iter().map(|[x, y, z]| Vec3 { x, y, z }));
+                                for &vertex in &copied {
+                                    displacements.push(displacements[vertex as usize]);
+                                }
+                            }
+                        }
                     }
                 }
-                
-                if let Some(prim_indices) = primitive.indices {
-                    let accessor = &accessors[prim_indices as usize];
-                    let view = &buffer_views[accessor.buffer_view.unwrap() as usize];
-                    let data = &buffers[view.buffer as usize].data[view.byte_offset as usize..view.byte_offset as usize + view.byte_length as usize];
-                    let data = reinterpret_slice::<u8, u16>(data);
-                    for value in data {
-                        indices.push(*value as u32);
+
+                // Later primitives may not have displaced every attribute.
+                for shape in &mut blend_shapes {
+                    for displacements in [&mut shape.positions, &mut shape.normals, &mut shape.tangents] {
+                        if !displacements.is_empty() {
+                            displacements.resize(vertices.len(), Vec3 { x: 0.0, y: 0.0, z: 0.0 });
+                        }
                     }
                 }
+
+                group_meshes.push(meshes.len());
+                meshes.push(Mesh {
+                    vertices,
+                    indices,
+                    primitive_type,
+                    material,
+                    blend_shapes
+                });
             }
 
-            meshes.push(Mesh {
-                vertices,
-                indices,
-                material: material.expect("Material is not defined") as usize
-            });
+            mesh_groups.push(group_meshes);
         }
 
         let mut materials = Vec::new();
 
-        if let Some(gltf_materials) = gltf.materials {
+        if let Some(gltf_materials) = &gltf.materials {
             for material in gltf_materials {
                 let mut textures = Vec::new();
 
-                let (base, metallic, roughness) = if let Some(pbr_mr) = material.pbr_metallic_roughness {
-                    if let Some(bct) = pbr_mr.base_color_texture {
-                        textures.push(TextureIndex {
-                            index: bct.index as usize,
-                            t_type: TextureType::Albedo
-                        });
+                let (base, metallic, roughness) = if let Some(pbr_mr) = &material.pbr_metallic_roughness {
+                    if let Some(bct) = &pbr_mr.base_color_texture {
+                        textures.push(get_texture_index(bct, TextureType::Albedo));
                     }
 
-                    if let Some(mrt) = pbr_mr.metallic_roughness_texture {
-                        textures.push(TextureIndex {
-                            index: mrt.index as usize,
-                            t_type: TextureType::Metallic
-                        });
-
-                        textures.push(TextureIndex {
-                            index: mrt.index as usize,
-                            t_type: TextureType::Roughness
-                        });
+                    if let Some(mrt) = &pbr_mr.metallic_roughness_texture {
+                        textures.push(get_texture_index(mrt, TextureType::Metallic));
+                        textures.push(get_texture_index(mrt, TextureType::Roughness));
                     }
 
                     (pbr_mr.base_color_factor, pbr_mr.metallic_factor, pbr_mr.roughness_factor)
@@ -203,25 +639,16 @@ impl Scene {
                 };
 
                 // TODO: Normal scale and occlusion strength.
-                if let Some(nmt) = material.normal_texture {
-                    textures.push(TextureIndex {
-                        index: nmt.index as usize,
-                        t_type: TextureType::Normal
-                    });
+                if let Some(nmt) = &material.normal_texture {
+                    textures.push(get_texture_index(nmt, TextureType::Normal));
                 }
 
-                if let Some(oct) = material.occlusion_texture {
-                    textures.push(TextureIndex {
-                        index: oct.index as usize,
-                        t_type: TextureType::AmbientOcclusion
-                    })
+                if let Some(oct) = &material.occlusion_texture {
+                    textures.push(get_texture_index(oct, TextureType::AmbientOcclusion));
                 }
 
-                if let Some(emt) = material.emissive_texture {
-                    textures.push(TextureIndex {
-                        index: emt.index as usize,
-                        t_type: TextureType::Emissive
-                    });
+                if let Some(emt) = &material.emissive_texture {
+                    textures.push(get_texture_index(emt, TextureType::Emissive));
                 }
 
                 let alpha_mode = match material.alpha_mode {
@@ -243,37 +670,175 @@ impl Scene {
             }
         }
 
+        if uses_default_material {
+            materials.push(Material::default());
+        }
+
         let mut textures = Vec::new();
 
-        if let Some(gltf_textures) = gltf.textures {
-            let images = gltf.images.expect("A texture is defined, but no images are. This behaviour is not supported.");
+        if let Some(gltf_textures) = &gltf.textures {
+            // Sources were checked against the images when they were imported.
+            let images = gltf.images.as_deref().unwrap_or_default();
 
-            for texture in gltf_textures {
-                let image = &images[texture.source.expect("A texture without a source is not supported.") as usize];
+            for (t, texture) in gltf_textures.iter().enumerate() {
+                let Some(source) = texture.source else {
+                    return Err(ImportError::Unsupported { path: format!("textures[{t}]"), message: "Textures without a source are not supported.".to_string() });
+                };
 
-                if let Some(uri) = &image.uri {
+                let image = &images[source as usize];
+
+                // Samplers were checked when they were imported.
+                let sampler = texture.sampler.map_or_else(Sampler::default, |sampler| get_sampler(&gltf.samplers.as_deref().unwrap_or_default()[sampler as usize]));
+
+                if let Some(embedded) = gltf.read_image(source)? {
+                    textures.push(Texture {
+                        path: None,
+                        data: Some(embedded.data),
+                        mime_type: embedded.mime_type,
+                        pixels: None,
+                        sampler
+                    });
+                } else if let Some(uri) = &image.uri {
+                    // URIs are stored percent-encoded, such as "Base%20Color.png".
                     textures.push(Texture {
-                        path: Some(uri.to_string()),
+                        path: Some(String::from_utf8_lossy(&importers::percent_decode(uri)).into_owned()),
                         data: None,
+                        mime_type: image.mime_type.clone(),
+                        pixels: None,
+                        sampler
+                    });
+                }
+            }
+        }
+
+        let mut nodes: Vec<Node> = gltf.nodes.as_deref().unwrap_or_default().iter().map(|node| Node {
+            name: node.name.clone(),
+            // Only one of these is set, the other is the identity.
+            transform: node.matrix * Mat4::from_translation_rotation_scale(node.translation, node.rotation, node.scale),
+            meshes: node.mesh.map_or_else(Vec::new, |mesh| mesh_groups[mesh as usize].clone()),
+            camera: None,
+            light: None,
+            parent: None,
+            children: node.children.as_deref().unwrap_or_default().iter().map(|&child| child as usize).collect()
+        }).collect();
+
+        for n in 0..nodes.len() {
+            for c in 0..nodes[n].children.len() {
+                let child = nodes[n].children[c];
+                if nodes[child].parent.is_some() {
+                    return Err(ImportError::InvalidData {
+                        path: format!("nodes[{n}].children[{c}]"),
+                        message: "Node has more than one parent.".to_string()
+                    });
+                }
+
+                nodes[child].parent = Some(n);
+            }
+        }
+
+        // Every node has at most one parent, so the nodes in a cycle can't be reached from a node without a parent.
+        let mut reached = vec![false; nodes.len()];
+        let mut stack: Vec<usize> = (0..nodes.len()).filter(|&n| nodes[n].parent.is_none()).collect();
+        while let Some(n) = stack.pop() {
+            reached[n] = true;
+            stack.extend_from_slice(&nodes[n].children);
+        }
+
+        if let Some(n) = reached.iter().position(|&r| !r) {
+            return Err(ImportError::InvalidData { path: format!("nodes[{n}]"), message: "Node is its own ancestor.".to_string() });
+        }
+
+        let mut scenes: Vec<SceneRoots> = Vec::new();
+        for (s, scene) in gltf.scenes.as_deref().unwrap_or_default().iter().enumerate() {
+            let roots: Vec<usize> = scene.nodes.as_deref().unwrap_or_default().iter().map(|&node| node as usize).collect();
+            if let Some(r) = roots.iter().position(|&root| nodes[root].parent.is_some()) {
+                return Err(ImportError::InvalidData { path: format!("scenes[{s}].nodes[{r}]"), message: "Root node has a parent.".to_string() });
+            }
+
+            scenes.push(SceneRoots { name: scene.name.clone(), nodes: roots });
+        }
+
+        if scenes.is_empty() && !nodes.is_empty() {
+            scenes.push(SceneRoots { name: None, nodes: (0..nodes.len()).filter(|&n| nodes[n].parent.is_none()).collect() });
+        }
+
+        let active_scene = gltf.scene.map(|scene| scene as usize).or(if scenes.is_empty() { None } else { Some(0) });
+
+        let mut cameras = Vec::new();
+
+        // A camera can be attached to more than one node, so each node gets its own camera.
+        if let (Some(gltf_nodes), Some(gltf_cameras)) = (&gltf.nodes, &gltf.cameras) {
+            for (i, node) in gltf_nodes.iter().enumerate() {
+                if let Some(camera) = node.camera {
+                    let camera = &gltf_cameras[camera as usize];
+
+                    let projection = match &camera.camera_type {
+                        importers::gltf::CameraType::Perspective(p) => Projection::Perspective {
+                            fov_y: p.yfov,
+                            aspect_ratio: p.aspect_ratio,
+                            near: p.znear,
+                            far: p.zfar
+                        },
+
+                        importers::gltf::CameraType::Orthographic(o) => Projection::Orthographic {
+                            x_mag: o.xmag,
+                            y_mag: o.ymag,
+                            near: o.znear,
+                            far: o.zfar
+                        }
+                    };
+
+                    nodes[i].camera = Some(cameras.len());
+                    cameras.push(Camera {
+                        name: camera.name.clone(),
+                        node: i,
+                        projection
                     });
-                } else {
-                    todo!("Embedded images are not yet supported.");
                 }
             }
         }
 
-        Ok(Scene { meshes, materials, textures })
+        let mut lights = Vec::new();
+
+        if let (Some(gltf_nodes), Some(gltf_lights)) = (&gltf.nodes, &gltf.lights) {
+            for (i, node) in gltf_nodes.iter().enumerate() {
+                if let Some(light) = node.light {
+                    let light = &gltf_lights[light as usize];
+
+                    let light_type = match &light.light_type {
+                        importers::gltf::LightType::Directional => LightType::Directional,
+                        importers::gltf::LightType::Point => LightType::Point,
+                        importers::gltf::LightType::Spot(spot) => LightType::Spot {
+                            inner_cone_angle: spot.inner_cone_angle,
+                            outer_cone_angle: spot.outer_cone_angle
+                        }
+                    };
+
+                    nodes[i].light = Some(lights.len());
+                    lights.push(Light {
+                        name: light.name.clone(),
+                        node: i,
+                        light_type,
+                        color: light.color,
+                        intensity: light.intensity,
+                        range: light.range
+                    });
+                }
+            }
+        }
+
+        Ok(Scene { meshes, materials, textures, cameras, lights, nodes, scenes, active_scene })
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct Vec3 {
     pub x: f32,
@@ -281,7 +846,7 @@ pub struct Vec3 {
     pub z: f32
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct Vec4 {
     pub x: f32,
@@ -290,7 +855,8 @@ pub struct Vec4 {
     pub w: f32
 }
 
-#[derive(Debug)]
+/// A column-major matrix: `m12` is the second element of the first column.
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct Mat4 {
     pub m11: f32,
@@ -311,17 +877,155 @@ pub struct Mat4 {
     pub m44: f32
 }
 
-#[derive(Debug)]
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        m11: 1.0, m12: 0.0, m13: 0.0, m14: 0.0,
+        m21: 0.0, m22: 1.0, m23: 0.0, m24: 0.0,
+        m31: 0.0, m32: 0.0, m33: 1.0, m34: 0.0,
+        m41: 0.0, m42: 0.0, m43: 0.0, m44: 1.0
+    };
+
+    /// Creates a matrix that scales, then rotates by the quaternion `rotation`, then translates.
+    pub fn from_translation_rotation_scale(translation: Vec3, rotation: Vec4, scale: Vec3) -> Mat4 {
+        let Vec4 { x, y, z, w } = rotation;
+
+        Mat4 {
+            m11: (1.0 - 2.0 * (y * y + z * z)) * scale.x,
+            m12: 2.0 * (x * y + z * w) * scale.x,
+            m13: 2.0 * (x * z - y * w) * scale.x,
+            m14: 0.0,
+            m21: 2.0 * (x * y - z * w) * scale.y,
+            m22: (1.0 - 2.0 * (x * x + z * z)) * scale.y,
+            m23: 2.0 * (y * z + x * w) * scale.y,
+            m24: 0.0,
+            m31: 2.0 * (x * z + y * w) * scale.z,
+            m32: 2.0 * (y * z - x * w) * scale.z,
+            m33: (1.0 - 2.0 * (x * x + y * y)) * scale.z,
+            m34: 0.0,
+            m41: translation.x,
+            m42: translation.y,
+            m43: translation.z,
+            m44: 1.0
+        }
+    }
+
+    fn to_columns(self) -> [[f32; 4]; 4] {
+        [
+            [self.m11, self.m12, self.m13, self.m14],
+            [self.m21, self.m22, self.m23, self.m24],
+            [self.m31, self.m32, self.m33, self.m34],
+            [self.m41, self.m42, self.m43, self.m44]
+        ]
+    }
+}
+
+impl std::ops::Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: Mat4) -> Mat4 {
+        let (a, b) = (self.to_columns(), rhs.to_columns());
+        let m = |c: usize, r: usize| (0..4).map(|k| a[k][r] * b[c][k]).sum();
+
+        Mat4 {
+            m11: m(0, 0), m12: m(0, 1), m13: m(0, 2), m14: m(0, 3),
+            m21: m(1, 0), m22: m(1, 1), m23: m(1, 2), m24: m(1, 3),
+            m31: m(2, 0), m32: m(2, 1), m33: m(2, 2), m34: m(2, 3),
+            m41: m(3, 0), m42: m(3, 1), m43: m(3, 2), m44: m(3, 3)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct VertexPositionColorTextureNormalTangentBitangent {
-    pub position:  Vec3,
-    pub color:     Vec4,
-    pub tex_coord: Vec2,
-    pub normal:    Vec3,
-    pub tangent:   Vec3,
-    pub bitangent: Vec3
+    pub position:    Vec3,
+    /// The `COLOR_0` attribute, or white.
+    pub color:       Vec4,
+    pub tex_coord:   Vec2,
+    /// The second texture coordinate set, usually for lightmaps.
+    pub tex_coord_1: Vec2,
+    pub normal:      Vec3,
+    pub tangent:     Vec3,
+    pub bitangent:   Vec3
+}
+
+fn get_primitive_type(topology: &importers::gltf::Topology) -> PrimitiveType {
+    use importers::gltf::Topology;
+
+    match topology {
+        Topology::Points => PrimitiveType::Points,
+        Topology::Lines | Topology::LineLoop | Topology::LineStrip => PrimitiveType::Lines,
+        Topology::Triangles | Topology::TriangleStrip | Topology::TriangleFan => PrimitiveType::Triangles
+    }
+}
+
+/// Converts strip, loop and fan indices to list indices, with the winding the glTF spec gives.
+fn get_primitive_list(topology: &importers::gltf::Topology, indices: &[u32]) -> Vec<u32> {
+    use importers::gltf::Topology;
+
+    let n = indices.len();
+    match topology {
+        Topology::Points | Topology::Lines | Topology::Triangles => indices.to_vec(),
+
+        Topology::LineStrip => indices.windows(2).flatten().copied().collect(),
+
+        Topology::LineLoop => {
+            let mut lines: Vec<u32> = indices.windows(2).flatten().copied().collect();
+            if n > 2 {
+                lines.extend([indices[n - 1], indices[0]]);
+            }
+
+            lines
+        }
+
+        // Every other triangle is flipped so they all wind the same way.
+        Topology::TriangleStrip => (0..n.saturating_sub(2))
+            .flat_map(|i| [indices[i], indices[i + 1 + i % 2], indices[i + 2 - i % 2]])
+            .collect(),
+
+        Topology::TriangleFan => (1..n.saturating_sub(1))
+            .flat_map(|i| [indices[i], indices[i + 1], indices[0]])
+            .collect()
+    }
+}
+
+fn get_sampler(sampler: &importers::gltf::Sampler) -> Sampler {
+    use importers::gltf::{TextureFilter as Filter, TextureWrapMode as Wrap};
+
+    let filter = |filter: &Filter| match filter {
+        Filter::Nearest => TextureFilter::Nearest,
+        Filter::Linear => TextureFilter::Linear,
+        Filter::NearestMipmapNearest => TextureFilter::NearestMipmapNearest,
+        Filter::LinearMipmapNearest => TextureFilter::LinearMipmapNearest,
+        Filter::NearestMipmapLinear => TextureFilter::NearestMipmapLinear,
+        Filter::LinearMipmapLinear => TextureFilter::LinearMipmapLinear
+    };
+
+    let wrap = |wrap: &Wrap| match wrap {
+        Wrap::ClampToEdge => WrapMode::ClampToEdge,
+        Wrap::MirroredRepeat => WrapMode::MirroredRepeat,
+        Wrap::Repeat => WrapMode::Repeat
+    };
+
+    Sampler {
+        mag_filter: sampler.mag_filter.as_ref().map(filter),
+        min_filter: sampler.min_filter.as_ref().map(filter),
+        wrap_s: wrap(&sampler.wrap_s),
+        wrap_t: wrap(&sampler.wrap_t)
+    }
 }
 
-fn reinterpret_slice<TFrom, TTo>(value: &[TFrom]) -> &[TTo] {
-    unsafe { std::slice::from_raw_parts(value.as_ptr() as *const TTo, value.len() / std::mem::size_of::<TTo>()) }
-}
\ No newline at end of file
+fn get_texture_index(info: &importers::gltf::TextureInfo, t_type: TextureType) -> TextureIndex {
+    let (tex_coord, transform) = if let Some(transform) = &info.transform {
+        (transform.tex_coord.unwrap_or(info.tex_coord), TextureTransform { offset: transform.offset, rotation: transform.rotation, scale: transform.scale })
+    } else {
+        (info.tex_coord, TextureTransform { offset: Vec2 { x: 0.0, y: 0.0 }, rotation: 0.0, scale: Vec2 { x: 1.0, y: 1.0 } })
+    };
+
+    TextureIndex {
+        index: info.index as usize,
+        t_type,
+        tex_coord: tex_coord as usize,
+        transform
+    }
+}
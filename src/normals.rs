@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use crate::{corners, NormalGeneration, Vec3, VertexPositionColorTextureNormalTangentBitangent as Vertex};
+
+type Vector = [f32; 3];
+
+fn sub(a: Vector, b: Vector) -> Vector {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: Vector, b: Vector) -> Vector {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn dot(a: Vector, b: Vector) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Returns `None` for zero length vectors, such as the normals of degenerate triangles.
+fn normalize(a: Vector) -> Option<Vector> {
+    let length = dot(a, a).sqrt();
+    (length > 0.0).then(|| a.map(|c| c / length))
+}
+
+/// Generates normals for the triangle list `indices`. Vertices whose triangles need different normals are
+/// split, see [`corners::assign`].
+pub(crate) fn generate(vertices: &mut Vec<Vertex>, indices: &mut [u32], mode: NormalGeneration) -> Vec<u32> {
+    let position = |index: u32| {
+        let Vec3 { x, y, z } = vertices[index as usize].position;
+        [x, y, z]
+    };
+
+    // The length of each face normal is twice the triangle's area.
+    let faces: Vec<Vector> = indices.chunks_exact(3)
+        .map(|t| cross(sub(position(t[1]), position(t[0])), sub(position(t[2]), position(t[0]))))
+        .collect();
+    let unit_faces: Vec<Vector> = faces.iter().map(|&face| normalize(face).unwrap_or([0.0; 3])).collect();
+
+    let normals = match mode {
+        NormalGeneration::Flat => (0..indices.len()).map(|corner| unit_faces[corner / 3]).collect(),
+
+        NormalGeneration::Smooth { crease_angle } => {
+            let min_cos = crease_angle.cos();
+
+            // Each corner is weighted by the triangle's area and the angle at the corner.
+            let weights: Vec<f32> = (0..indices.len()).map(|corner| {
+                let triangle = &indices[corner / 3 * 3..][..3];
+                let (prev, this, next) = (triangle[(corner + 2) % 3], triangle[corner % 3], triangle[(corner + 1) % 3]);
+
+                let angle = match (normalize(sub(position(next), position(this))), normalize(sub(position(prev), position(this)))) {
+                    (Some(a), Some(b)) => dot(a, b).clamp(-1.0, 1.0).acos(),
+                    _ => 0.0
+                };
+
+                angle * dot(faces[corner / 3], faces[corner / 3]).sqrt()
+            }).collect();
+
+            // Vertices are smoothed with every vertex at the same position, so seams in other attributes stay smooth.
+            let mut by_position: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+            for (corner, &index) in indices.iter().enumerate() {
+                by_position.entry(position(index).map(f32::to_bits)).or_default().push(corner);
+            }
+
+            (0..indices.len()).map(|corner| {
+                let face = unit_faces[corner / 3];
+                let mut normal = [0.0; 3];
+                for &other in &by_position[&position(indices[corner]).map(f32::to_bits)] {
+                    let other_face = unit_faces[other / 3];
+                    if dot(face, other_face) >= min_cos {
+                        normal = [0, 1, 2].map(|c| normal[c] + other_face[c] * weights[other]);
+                    }
+                }
+
+                normalize(normal).unwrap_or(face)
+            }).collect()
+        }
+    };
+
+    corners::assign(vertices, indices, normals, |vertex, [x, y, z]| vertex.normal = Vec3 { x, y, z })
+}
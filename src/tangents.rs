@@ -0,0 +1,69 @@
+use crate::{corners, Vec3, VertexPositionColorTextureNormalTangentBitangent as Vertex};
+
+/// The triangles of a mesh, as MikkTSpace sees them. Tangents are stored per triangle corner.
+struct Triangles<'a> {
+    vertices: &'a [Vertex],
+    indices:  &'a [u32],
+    tangents: Vec<[f32; 4]>
+}
+
+impl Triangles<'_> {
+    fn vertex(&self, face: usize, vert: usize) -> &Vertex {
+        &self.vertices[self.indices[face * 3 + vert] as usize]
+    }
+}
+
+impl bevy_mikktspace::Geometry for Triangles<'_> {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        let Vec3 { x, y, z } = self.vertex(face, vert).position;
+        [x, y, z]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        let Vec3 { x, y, z } = self.vertex(face, vert).normal;
+        [x, y, z]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        let tex_coord = self.vertex(face, vert).tex_coord;
+        [tex_coord.x, tex_coord.y]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.tangents[face * 3 + vert] = tangent;
+    }
+}
+
+/// Generates MikkTSpace tangents and bitangents for the triangle list `indices`, using the first texture
+/// coordinate set. Vertices whose triangles need different tangents are split, see [`corners::assign`].
+pub(crate) fn generate(vertices: &mut Vec<Vertex>, indices: &mut [u32]) -> Vec<u32> {
+    let mut triangles = Triangles { vertices, indices, tangents: vec![[0.0; 4]; indices.len()] };
+
+    // Degenerate geometry is left without tangents.
+    if !bevy_mikktspace::generate_tangents(&mut triangles) {
+        return Vec::new();
+    }
+
+    let tangents = triangles.tangents;
+    corners::assign(vertices, indices, tangents, set_tangent)
+}
+
+/// Sets a vertex's tangent from a glTF style tangent, where `w` is the handedness of the bitangent.
+pub(crate) fn set_tangent(vertex: &mut Vertex, [x, y, z, w]: [f32; 4]) {
+    let n = vertex.normal;
+
+    vertex.tangent = Vec3 { x, y, z };
+    vertex.bitangent = Vec3 {
+        x: (n.y * z - n.z * y) * w,
+        y: (n.z * x - n.x * z) * w,
+        z: (n.x * y - n.y * x) * w
+    };
+}
@@ -0,0 +1,276 @@
+use impasse::importers::{ImportError, Importer, gltf::Gltf};
+
+fn import_json(name: &str, json: &str) -> Result<Gltf, ImportError> {
+    let path = std::env::temp_dir().join(format!("impasse_{name}.gltf"));
+    std::fs::write(&path, json).unwrap();
+
+    Gltf::import(path.to_str().unwrap())
+}
+
+#[test]
+fn test_skins() {
+    let gltf = import_json("skins", r#"{
+        "asset": { "version": "2.0" },
+        "nodes": [ { "skin": 0, "children": [1] }, { "children": [2] }, {} ],
+        "accessors": [ { "componentType": 5126, "count": 1, "type": "MAT4" } ],
+        "skins": [ { "inverseBindMatrices": 0, "skeleton": 1, "joints": [1, 2], "name": "Armature" } ]
+    }"#).unwrap();
+
+    let skins = gltf.skins.unwrap();
+    assert_eq!(skins.len(), 1);
+    assert_eq!(skins[0].joints, vec![1, 2]);
+    assert_eq!(skins[0].skeleton, Some(1));
+    assert_eq!(skins[0].inverse_bind_matrices, Some(0));
+    assert_eq!(skins[0].name.as_deref(), Some("Armature"));
+}
+
+#[test]
+fn test_skins_out_of_range() {
+    let joint = import_json("skins_bad_joint", r#"{
+        "asset": { "version": "2.0" },
+        "nodes": [ {}, {} ],
+        "skins": [ { "joints": [0, 5] } ]
+    }"#);
+    assert!(joint.is_err());
+
+    let skin = import_json("skins_bad_node", r#"{
+        "asset": { "version": "2.0" },
+        "nodes": [ { "skin": 1 } ],
+        "skins": [ { "joints": [0] } ]
+    }"#);
+    assert!(skin.is_err());
+}
+
+#[test]
+fn test_animations() {
+    use impasse::importers::gltf::{AnimationPath, Interpolation};
+
+    let gltf = import_json("animations", r#"{
+        "asset": { "version": "2.0" },
+        "nodes": [ {}, {} ],
+        "accessors": [ { "componentType": 5126, "count": 1, "type": "SCALAR" }, { "componentType": 5126, "count": 1, "type": "VEC4" }, { "componentType": 5126, "count": 1, "type": "SCALAR" } ],
+        "animations": [ {
+            "name": "Walk",
+            "channels": [
+                { "sampler": 0, "target": { "node": 1, "path": "rotation" } },
+                { "sampler": 1, "target": { "node": 0, "path": "weights" } }
+            ],
+            "samplers": [
+                { "input": 0, "output": 1 },
+                { "input": 0, "output": 2, "interpolation": "CUBICSPLINE" }
+            ]
+        } ]
+    }"#).unwrap();
+
+    let animations = gltf.animations.unwrap();
+    let animation = &animations[0];
+    assert_eq!(animation.name.as_deref(), Some("Walk"));
+    assert_eq!(animation.channels.len(), 2);
+    assert_eq!(animation.channels[0].target.node, Some(1));
+    assert!(matches!(animation.channels[0].target.path, AnimationPath::Rotation));
+    assert!(matches!(animation.channels[1].target.path, AnimationPath::Weights));
+    assert!(matches!(animation.samplers[0].interpolation, Interpolation::Linear));
+    assert!(matches!(animation.samplers[1].interpolation, Interpolation::CubicSpline));
+    assert_eq!(animation.samplers[1].output, 2);
+
+    let bad_sampler = import_json("animations_bad_sampler", r#"{
+        "asset": { "version": "2.0" },
+        "nodes": [ {} ],
+        "accessors": [ { "componentType": 5126, "count": 1, "type": "SCALAR" }, { "componentType": 5126, "count": 1, "type": "VEC4" }, { "componentType": 5126, "count": 1, "type": "SCALAR" } ],
+        "animations": [ {
+            "channels": [ { "sampler": 3, "target": { "node": 0, "path": "scale" } } ],
+            "samplers": [ { "input": 0, "output": 1 } ]
+        } ]
+    }"#);
+    assert!(bad_sampler.is_err());
+}
+
+#[test]
+fn test_animations_unrecognized() {
+    let interpolation = import_json("animations_bad_interpolation", r#"{
+        "asset": { "version": "2.0" },
+        "nodes": [ {} ],
+        "accessors": [ { "componentType": 5126, "count": 1, "type": "SCALAR" }, { "componentType": 5126, "count": 1, "type": "VEC4" }, { "componentType": 5126, "count": 1, "type": "SCALAR" } ],
+        "animations": [ {
+            "channels": [ { "sampler": 0, "target": { "node": 0, "path": "scale" } } ],
+            "samplers": [ { "input": 0, "output": 1, "interpolation": "BEZIER" } ]
+        } ]
+    }"#);
+    assert!(interpolation.is_err());
+
+    let path = import_json("animations_bad_path", r#"{
+        "asset": { "version": "2.0" },
+        "nodes": [ {} ],
+        "accessors": [ { "componentType": 5126, "count": 1, "type": "SCALAR" }, { "componentType": 5126, "count": 1, "type": "VEC4" }, { "componentType": 5126, "count": 1, "type": "SCALAR" } ],
+        "animations": [ {
+            "channels": [ { "sampler": 0, "target": { "node": 0, "path": "pointer" } } ],
+            "samplers": [ { "input": 0, "output": 1 } ]
+        } ]
+    }"#);
+    assert!(path.is_err());
+}
+
+#[test]
+fn test_meshopt_buffer_views() {
+    use base64::Engine;
+
+    // Three octahedral normals, with the byte groups packed into 4 and 2 bits and escaped where they don't fit.
+    let mut data = vec![0xA0, 0x02, 0x0F, 0xF0, 0, 0, 0, 0, 0, 0, 254, 253, 0x01, 0x0C, 0, 0, 0, 253, 0x00, 0x00];
+    data.extend_from_slice(&[0; 28]);
+    data.extend_from_slice(&[0, 0, 127, 0]);
+    let normals_length = data.len();
+    // The index sequence (0, 1, 2), each relative to the one before.
+    data.extend_from_slice(&[0xD1, 0, 4, 4, 0, 0, 0, 0]);
+
+    let gltf = import_json("meshopt", &format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "buffers": [
+            {{ "uri": "data:application/octet-stream;base64,{}", "byteLength": {} }},
+            {{ "byteLength": 24, "extensions": {{ "EXT_meshopt_compression": {{ "fallback": true }} }} }}
+        ],
+        "bufferViews": [
+            {{ "buffer": 1, "byteLength": 12, "byteStride": 4,
+               "extensions": {{ "EXT_meshopt_compression": {{ "buffer": 0, "byteLength": {normals_length}, "byteStride": 4, "count": 3, "mode": "ATTRIBUTES", "filter": "OCTAHEDRAL" }} }} }},
+            {{ "buffer": 1, "byteOffset": 12, "byteLength": 12,
+               "extensions": {{ "EXT_meshopt_compression": {{ "buffer": 0, "byteOffset": {normals_length}, "byteLength": 8, "byteStride": 4, "count": 3, "mode": "INDICES" }} }} }}
+        ]
+    }}"#, base64::engine::general_purpose::STANDARD.encode(&data), data.len())).unwrap();
+
+    let views = gltf.buffer_views.as_ref().unwrap();
+    let buffers = gltf.buffers.as_ref().unwrap();
+    assert_eq!(views[0].byte_offset, 0);
+    assert_eq!(buffers[views[0].buffer as usize].data, [0, 0, 127, 0, 127, 0, 0, 0, 0, 129, 0, 0]);
+    assert_eq!(buffers[views[1].buffer as usize].data, [0, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0]);
+}
+
+#[test]
+fn test_meshopt_invalid() {
+    let mode = import_json("meshopt_bad_mode", r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [ { "uri": "data:application/octet-stream;base64,AAAA", "byteLength": 3 } ],
+        "bufferViews": [ { "buffer": 0, "byteLength": 3,
+            "extensions": { "EXT_meshopt_compression": { "buffer": 0, "byteLength": 3, "byteStride": 4, "count": 1, "mode": "STRIPS" } } } ]
+    }"#);
+    assert!(mode.is_err());
+
+    let truncated = import_json("meshopt_truncated", r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [ { "uri": "data:application/octet-stream;base64,oAAA", "byteLength": 3 } ],
+        "bufferViews": [ { "buffer": 0, "byteLength": 3,
+            "extensions": { "EXT_meshopt_compression": { "buffer": 0, "byteLength": 3, "byteStride": 4, "count": 1, "mode": "ATTRIBUTES" } } } ]
+    }"#);
+    assert!(matches!(truncated.unwrap_err(), ImportError::InvalidData { .. }));
+}
+
+#[test]
+fn test_import_errors() {
+    let index = import_json("errors_bad_index", r#"{
+        "asset": { "version": "2.0" },
+        "accessors": [ { "componentType": 5126, "count": 3, "type": "VEC3" } ],
+        "meshes": [ { "primitives": [ { "attributes": { "POSITION": 0 }, "indices": 4 } ] } ]
+    }"#).unwrap_err();
+    assert!(matches!(index, ImportError::InvalidIndex { index: 4, .. }));
+    assert_eq!(index.path(), Some("meshes[0].primitives[0].indices"));
+
+    let missing = import_json("errors_missing_field", r#"{
+        "asset": { "version": "2.0" },
+        "accessors": [ { "componentType": 5126, "type": "VEC3" } ]
+    }"#).unwrap_err();
+    assert!(matches!(missing, ImportError::MissingField { .. }));
+    assert_eq!(missing.path(), Some("accessors[0].count"));
+
+    let wrong_type = import_json("errors_wrong_type", r#"{
+        "asset": { "version": "2.0" },
+        "nodes": [ { "translation": [1.0, 2.0] } ]
+    }"#).unwrap_err();
+    assert!(matches!(wrong_type, ImportError::InvalidType { .. }));
+    assert_eq!(wrong_type.path(), Some("nodes[0].translation"));
+
+    let filter = import_json("errors_bad_filter", r#"{
+        "asset": { "version": "2.0" },
+        "samplers": [ { "magFilter": 1234 } ]
+    }"#).unwrap_err();
+    assert!(matches!(filter, ImportError::InvalidData { .. }));
+    assert_eq!(filter.path(), Some("samplers[0].magFilter"));
+
+    let json = import_json("errors_bad_json", "{ \"asset\": ").unwrap_err();
+    assert!(matches!(json, ImportError::Json(_)));
+}
+
+#[test]
+fn test_read_accessor() {
+    use base64::Engine;
+
+    // Two interleaved vertices of a float position and a normalized byte color, padded to 16 bytes,
+    // after 4 bytes of unrelated data. The byte indices follow the vertices.
+    let mut data = vec![0xFF; 4];
+    for (position, color) in [([1.0f32, 2.0, 3.0], [255u8, 0, 51, 0]), ([-1.0, 0.5, 0.0], [0, 255, 0, 0])] {
+        data.extend(position.iter().flat_map(|p| p.to_le_bytes()));
+        data.extend_from_slice(&color);
+    }
+    data.extend_from_slice(&[2, 0, 1, 0]);
+
+    let gltf = import_json("read_accessor", &format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "buffers": [ {{ "uri": "data:application/octet-stream;base64,{}", "byteLength": {} }} ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 4, "byteLength": 32, "byteStride": 16 }},
+            {{ "buffer": 0, "byteOffset": 36, "byteLength": 4 }}
+        ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 2, "type": "VEC3" }},
+            {{ "bufferView": 0, "byteOffset": 12, "componentType": 5121, "normalized": true, "count": 2, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5121, "count": 3, "type": "SCALAR" }},
+            {{ "componentType": 5126, "count": 2, "type": "VEC2" }},
+            {{ "componentType": 5126, "count": 2147483647, "type": "MAT4" }}
+        ]
+    }}"#, base64::engine::general_purpose::STANDARD.encode(&data), data.len())).unwrap();
+
+    assert_eq!(gltf.read_accessor::<f32, 3>(0).unwrap(), [[1.0, 2.0, 3.0], [-1.0, 0.5, 0.0]]);
+    assert_eq!(gltf.read_accessor::<f32, 3>(1).unwrap(), [[1.0, 0.0, 0.2], [0.0, 1.0, 0.0]]);
+    assert_eq!(gltf.read_accessor::<u32, 1>(2).unwrap(), [[2], [0], [1]]);
+    // An accessor without a buffer view is all zeros.
+    assert_eq!(gltf.read_accessor::<f32, 2>(3).unwrap(), [[0.0, 0.0], [0.0, 0.0]]);
+    // Without a buffer view nothing bounds the count, so accessors too large to allocate are errors.
+    let huge = gltf.read_accessor::<f32, 16>(4);
+    assert!(matches!(huge, Err(ImportError::InvalidData { ref path, .. }) if path == "accessors[4].count"));
+
+    assert!(matches!(gltf.read_accessor::<f32, 2>(0), Err(ImportError::InvalidData { .. })));
+    assert!(matches!(gltf.read_accessor::<u32, 3>(0), Err(ImportError::InvalidData { .. })));
+    assert!(matches!(gltf.read_accessor::<f32, 3>(5), Err(ImportError::InvalidIndex { .. })));
+}
+
+#[test]
+fn test_read_accessor_matrices() {
+    use base64::Engine;
+
+    // Matrix columns start on 4 byte boundaries, so byte MAT2 and short MAT3 columns are padded. Padding is 0xFF.
+    let mut data = Vec::new();
+    for column in [[1u8, 2], [3, 4], [5, 6], [7, 8]] {
+        data.extend_from_slice(&column);
+        data.extend_from_slice(&[0xFF; 2]);
+    }
+    for column in [[1u16, 2, 3], [4, 5, 6], [7, 8, 9]] {
+        data.extend(column.iter().flat_map(|c| c.to_le_bytes()));
+        data.extend_from_slice(&[0xFF; 2]);
+    }
+    data.extend(1..=16u8);
+
+    let gltf = import_json("read_accessor_matrices", &format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "buffers": [ {{ "uri": "data:application/octet-stream;base64,{}", "byteLength": {} }} ],
+        "bufferViews": [ {{ "buffer": 0, "byteLength": {} }} ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5121, "count": 2, "type": "MAT2" }},
+            {{ "bufferView": 0, "byteOffset": 16, "componentType": 5123, "count": 1, "type": "MAT3" }},
+            {{ "bufferView": 0, "byteOffset": 40, "componentType": 5121, "count": 1, "type": "MAT4" }},
+            {{ "bufferView": 0, "byteOffset": 40, "componentType": 5121, "count": 3, "type": "MAT3" }}
+        ]
+    }}"#, base64::engine::general_purpose::STANDARD.encode(&data), data.len(), data.len())).unwrap();
+
+    assert_eq!(gltf.read_accessor::<u32, 4>(0).unwrap(), [[1, 2, 3, 4], [5, 6, 7, 8]]);
+    assert_eq!(gltf.read_accessor::<u32, 9>(1).unwrap(), [[1, 2, 3, 4, 5, 6, 7, 8, 9]]);
+    assert_eq!(gltf.read_accessor::<u32, 16>(2).unwrap(), [std::array::from_fn(|i| i as u32 + 1)]);
+    // Three padded byte MAT3s take 36 bytes, which is more than the 16 that are left.
+    assert!(matches!(gltf.read_accessor::<u32, 9>(3), Err(ImportError::InvalidData { .. })));
+}
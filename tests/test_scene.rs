@@ -1,6 +1,1151 @@
+use base64::Engine;
+use impasse::importers::ImportError;
+
 #[test]
 pub fn test_scene() {
     let scene = impasse::Scene::from_gltf("/home/ollie/Downloads/ionthrusterconcept01.gltf").unwrap();
 
     println!("{:#?}", scene);
-}
\ No newline at end of file
+}
+
+/// Returns a triangle's positions (36 bytes), followed by its indices (6 bytes).
+fn triangle_data() -> Vec<u8> {
+    let mut data = Vec::new();
+    for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    for index in [0u16, 1, 2, 0] {
+        data.extend_from_slice(&index.to_le_bytes());
+    }
+
+    data
+}
+
+/// Returns the JSON for a buffer containing the triangle data.
+fn triangle_buffer() -> String {
+    buffer(&triangle_data())
+}
+
+/// Returns the JSON for a buffer embedding the given data as a data URI.
+fn buffer(data: &[u8]) -> String {
+    format!(r#"{{ "uri": "data:application/octet-stream;base64,{}", "byteLength": {} }}"#, base64::engine::general_purpose::STANDARD.encode(data), data.len())
+}
+
+fn load_json(name: &str, json: &str) -> Result<impasse::Scene, ImportError> {
+    let path = std::env::temp_dir().join(format!("impasse_scene_{name}.gltf"));
+    std::fs::write(&path, json).unwrap();
+
+    impasse::Scene::from_gltf(path.to_str().unwrap())
+}
+
+/// Loads a glTF containing a single triangle, merging the given JSON members into the top level object.
+/// Duplicate members replace the earlier ones, so `extra` can override the default material.
+fn load_triangle(name: &str, extra: &str) -> Result<impasse::Scene, ImportError> {
+    load_json(name, &format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "buffers": [ {} ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+            {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+        ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+        ],
+        "materials": [ {{ }} ],
+        "meshes": [ {{ "primitives": [ {{ "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0 }} ] }} ]
+        {extra}
+    }}"#, triangle_buffer()))
+}
+
+#[test]
+fn test_scene_cameras() {
+    let scene = load_triangle("cameras", r#",
+        "nodes": [ { "mesh": 0 }, { "camera": 0 }, { "camera": 1 }, { "camera": 0 } ],
+        "cameras": [
+            { "type": "perspective", "perspective": { "yfov": 0.8, "znear": 0.1, "aspectRatio": 1.5 } },
+            { "type": "orthographic", "name": "Top", "orthographic": { "xmag": 2.0, "ymag": 3.0, "znear": 0.01, "zfar": 100.0 } }
+        ]
+    "#).unwrap();
+
+    assert_eq!(scene.cameras.len(), 3);
+    assert_eq!(scene.cameras[0].node, 1);
+    assert_eq!(scene.cameras[2].node, 3);
+
+    match scene.cameras[0].projection {
+        impasse::Projection::Perspective { fov_y, aspect_ratio, near, far } => {
+            assert_eq!(fov_y, 0.8);
+            assert_eq!(aspect_ratio, Some(1.5));
+            assert_eq!(near, 0.1);
+            assert_eq!(far, None);
+        },
+        _ => panic!("Expected a perspective camera.")
+    }
+
+    assert_eq!(scene.cameras[1].name.as_deref(), Some("Top"));
+    assert!(matches!(scene.cameras[1].projection, impasse::Projection::Orthographic { x_mag, y_mag, .. } if x_mag == 2.0 && y_mag == 3.0));
+}
+
+#[test]
+fn test_scene_unrecognized_camera() {
+    let scene = load_triangle("bad_camera", r#",
+        "nodes": [ { "camera": 0 } ],
+        "cameras": [ { "type": "fisheye" } ]
+    "#);
+    assert!(scene.is_err());
+}
+
+#[test]
+fn test_scene_blend_shapes() {
+    let scene = load_triangle("blend_shapes", r#",
+        "meshes": [ {
+            "primitives": [ {
+                "attributes": { "POSITION": 0 },
+                "indices": 1,
+                "material": 0,
+                "targets": [ { "POSITION": 0 }, { "NORMAL": 0 }, { "POSITION": 0 } ]
+            } ],
+            "weights": [ 0.25, 0.75 ],
+            "extras": { "targetNames": [ "Smile", "Frown", 3 ] }
+        } ]
+    "#).unwrap();
+
+    let shapes = &scene.meshes[0].blend_shapes;
+    assert_eq!(shapes.len(), 3);
+
+    assert_eq!(shapes[0].name.as_deref(), Some("Smile"));
+    assert_eq!(shapes[0].weight, 0.25);
+    assert_eq!(shapes[0].positions.len(), 3);
+    assert_eq!(shapes[0].positions[1].x, 1.0);
+    assert!(shapes[0].normals.is_empty());
+
+    assert_eq!(shapes[1].name.as_deref(), Some("Frown"));
+    assert_eq!(shapes[1].weight, 0.75);
+    assert!(shapes[1].positions.is_empty());
+    assert_eq!(shapes[1].normals[2].y, 1.0);
+
+    assert_eq!(shapes[2].name, None);
+    assert_eq!(shapes[2].weight, 0.0);
+}
+
+#[test]
+fn test_scene_sparse_blend_shapes() {
+    // The target has no buffer view, so only the vertex the sparse index points at is displaced.
+    let scene = load_triangle("sparse_blend_shapes", r#",
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" },
+            { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" },
+            { "componentType": 5126, "count": 3, "type": "VEC3", "sparse": {
+                "count": 1,
+                "indices": { "bufferView": 1, "byteOffset": 2, "componentType": 5123 },
+                "values": { "bufferView": 0, "byteOffset": 12 }
+            } }
+        ],
+        "meshes": [ { "primitives": [ { "attributes": { "POSITION": 0 }, "indices": 1, "material": 0, "targets": [ { "POSITION": 2 } ] } ] } ]
+    "#).unwrap();
+
+    let positions = &scene.meshes[0].blend_shapes[0].positions;
+    assert_eq!(positions.len(), 3);
+    assert_eq!((positions[0].x, positions[1].x, positions[2].x), (0.0, 1.0, 0.0));
+
+    // The index is the bits of 1.0, which is far past the end of the accessor.
+    let out_of_range = load_triangle("sparse_out_of_range", r#",
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "sparse": {
+                "count": 1,
+                "indices": { "bufferView": 0, "byteOffset": 12, "componentType": 5125 },
+                "values": { "bufferView": 0 }
+            } },
+            { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }
+        ]
+    "#);
+    assert!(matches!(out_of_range, Err(ImportError::InvalidData { .. })));
+}
+
+#[test]
+fn test_scene_blend_shapes_mismatched() {
+    let scene = load_triangle("blend_shapes_mismatched", r#",
+        "meshes": [ {
+            "primitives": [
+                { "attributes": { "POSITION": 0 }, "material": 0, "targets": [ { "POSITION": 0 } ] },
+                { "attributes": { "POSITION": 0 }, "material": 0 }
+            ]
+        } ]
+    "#);
+    assert!(scene.is_err());
+}
+
+#[test]
+fn test_scene_lights() {
+    let scene = load_triangle("lights", r#",
+        "extensionsUsed": [ "KHR_lights_punctual" ],
+        "extensions": {
+            "KHR_lights_punctual": {
+                "lights": [
+                    { "type": "directional", "color": [1.0, 0.5, 0.25], "intensity": 3.0, "name": "Sun" },
+                    { "type": "spot", "range": 10.0, "spot": { "innerConeAngle": 0.2 } }
+                ]
+            }
+        },
+        "nodes": [
+            { "mesh": 0 },
+            { "extensions": { "KHR_lights_punctual": { "light": 1 } } },
+            { "extensions": { "KHR_lights_punctual": { "light": 0 } } }
+        ]
+    "#).unwrap();
+
+    assert_eq!(scene.lights.len(), 2);
+
+    let spot = &scene.lights[0];
+    assert_eq!(spot.node, 1);
+    assert_eq!(spot.range, Some(10.0));
+    assert_eq!(spot.intensity, 1.0);
+    match spot.light_type {
+        impasse::LightType::Spot { inner_cone_angle, outer_cone_angle } => {
+            assert_eq!(inner_cone_angle, 0.2);
+            assert_eq!(outer_cone_angle, std::f32::consts::FRAC_PI_4);
+        },
+        _ => panic!("Expected a spot light.")
+    }
+
+    let sun = &scene.lights[1];
+    assert_eq!(sun.node, 2);
+    assert_eq!(sun.name.as_deref(), Some("Sun"));
+    assert!(matches!(sun.light_type, impasse::LightType::Directional));
+    assert_eq!(sun.color.y, 0.5);
+    assert_eq!(sun.intensity, 3.0);
+    assert_eq!(sun.range, None);
+}
+
+#[test]
+fn test_scene_invalid_lights() {
+    let light_type = load_triangle("bad_light_type", r#",
+        "extensions": { "KHR_lights_punctual": { "lights": [ { "type": "area" } ] } },
+        "nodes": [ { "extensions": { "KHR_lights_punctual": { "light": 0 } } } ]
+    "#);
+    assert!(light_type.is_err());
+
+    let no_index = load_triangle("bad_light_index", r#",
+        "extensions": { "KHR_lights_punctual": { "lights": [ { "type": "point" } ] } },
+        "nodes": [ { "extensions": { "KHR_lights_punctual": { } } } ]
+    "#);
+    assert!(no_index.is_err());
+}
+
+#[test]
+fn test_scene_embedded_images() {
+    // The second image is the triangle's index bytes, standing in for an encoded image.
+    let scene = load_triangle("embedded_images", r#",
+        "images": [
+            { "uri": "data:image/png;base64,iVBORw==" },
+            { "bufferView": 1, "mimeType": "image/jpeg" },
+            { "uri": "Base%20Color.png" }
+        ],
+        "textures": [ { "source": 0 }, { "source": 1 }, { "source": 2 } ]
+    "#).unwrap();
+
+    let textures = &scene.textures;
+    assert_eq!(textures[0].data.as_deref(), Some(&[0x89, b'P', b'N', b'G'][..]));
+    assert_eq!(textures[0].mime_type.as_deref(), Some("image/png"));
+    assert_eq!(textures[0].path, None);
+
+    assert_eq!(textures[1].data.as_deref(), Some(&[0, 0, 1, 0, 2, 0][..]));
+    assert_eq!(textures[1].mime_type.as_deref(), Some("image/jpeg"));
+
+    assert_eq!(textures[2].path.as_deref(), Some("Base Color.png"));
+    assert_eq!(textures[2].data, None);
+
+    let invalid = load_triangle("embedded_images_invalid", r#",
+        "images": [ { "uri": "data:image/png;base64,!!!!" } ],
+        "textures": [ { "source": 0 } ]
+    "#);
+    assert!(matches!(invalid, Err(ImportError::InvalidData { ref path, .. }) if path == "images[0].uri"));
+}
+
+#[test]
+fn test_scene_samplers() {
+    use impasse::{TextureFilter, WrapMode};
+
+    let scene = load_triangle("samplers", r#",
+        "images": [ { "uri": "pixels.png" } ],
+        "samplers": [ { "magFilter": 9728, "minFilter": 9984, "wrapS": 33071, "wrapT": 33648 } ],
+        "textures": [ { "source": 0, "sampler": 0 }, { "source": 0 } ]
+    "#).unwrap();
+
+    let pixel_art = scene.textures[0].sampler;
+    assert_eq!(pixel_art.mag_filter, Some(TextureFilter::Nearest));
+    assert_eq!(pixel_art.min_filter, Some(TextureFilter::NearestMipmapNearest));
+    assert_eq!((pixel_art.wrap_s, pixel_art.wrap_t), (WrapMode::ClampToEdge, WrapMode::MirroredRepeat));
+
+    assert_eq!(scene.textures[1].sampler, impasse::Sampler::default());
+    assert_eq!(scene.textures[1].sampler.wrap_s, WrapMode::Repeat);
+}
+
+#[test]
+fn test_scene_primitive_materials() {
+    // Three triangles sharing the same data: two with the first material and one without a material.
+    let scene = load_triangle("primitive_materials", r#",
+        "materials": [ { "doubleSided": true }, { "alphaMode": "BLEND" } ],
+        "meshes": [
+            { "primitives": [ { "attributes": { "POSITION": 0 }, "indices": 1, "material": 1 } ] },
+            { "primitives": [
+                { "attributes": { "POSITION": 0 }, "indices": 1, "material": 0 },
+                { "attributes": { "POSITION": 0 }, "indices": 1 },
+                { "attributes": { "POSITION": 0 }, "indices": 1, "material": 0 }
+            ] }
+        ],
+        "nodes": [ { "mesh": 1 }, { "mesh": 0 } ]
+    "#).unwrap();
+
+    assert_eq!(scene.materials.len(), 3);
+    let default = &scene.materials[2];
+    assert!(matches!(default.alpha_mode, impasse::AlphaMode::Opaque));
+    assert_eq!((default.metallic_factor, default.roughness_factor, default.alpha_cutoff), (1.0, 1.0, 0.5));
+    assert!(!default.double_sided);
+
+    assert_eq!(scene.meshes.len(), 3);
+    assert_eq!(scene.meshes.iter().map(|m| m.material).collect::<Vec<_>>(), vec![1, 0, 2]);
+    assert_eq!(scene.meshes[1].indices.len(), 6);
+    assert_eq!(scene.meshes[2].indices.len(), 3);
+
+    assert_eq!(scene.nodes[0].meshes, vec![1, 2]);
+    assert_eq!(scene.nodes[1].meshes, vec![0]);
+
+    // Files where every primitive has a material don't get a default material.
+    assert_eq!(load_triangle("primitive_materials_none", "").unwrap().materials.len(), 1);
+}
+
+#[test]
+fn test_scene_merged_primitives() {
+    let scene = load_triangle("merged_primitives", r#",
+        "meshes": [ { "primitives": [
+            { "attributes": { "POSITION": 0 }, "indices": 1, "material": 0, "targets": [ { "NORMAL": 0 } ] },
+            { "attributes": { "POSITION": 0 }, "indices": 1, "material": 0, "targets": [ { "POSITION": 0 } ] }
+        ] } ]
+    "#).unwrap();
+
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.vertices.len(), 6);
+    assert_eq!(mesh.vertices[4].position.x, 1.0);
+    assert_eq!(mesh.indices, vec![0, 1, 2, 3, 4, 5]);
+
+    // Displacements line up with the vertices, with zeros for primitives that don't displace an attribute.
+    let shape = &mesh.blend_shapes[0];
+    assert_eq!((shape.positions.len(), shape.normals.len()), (6, 6));
+    assert_eq!((shape.positions[1].x, shape.positions[4].x), (0.0, 1.0));
+    assert_eq!((shape.normals[2].y, shape.normals[5].y), (1.0, 0.0));
+
+    let mismatched = load_triangle("merged_primitives_mismatched", r#",
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" },
+            { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" },
+            { "bufferView": 0, "componentType": 5126, "count": 2, "type": "VEC3" }
+        ],
+        "meshes": [ { "primitives": [ { "attributes": { "POSITION": 0, "NORMAL": 2 }, "indices": 1, "material": 0 } ] } ]
+    "#);
+    assert_eq!(mismatched.unwrap_err().path(), Some("meshes[0].primitives[0].attributes.NORMAL"));
+
+    // The second index is the high half of 1.0f, which is past the last vertex.
+    let out_of_range = load_triangle("merged_primitives_out_of_range", r#",
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" },
+            { "bufferView": 0, "byteOffset": 12, "componentType": 5123, "count": 3, "type": "SCALAR" }
+        ]
+    "#);
+    assert_eq!(out_of_range.unwrap_err().path(), Some("meshes[0].primitives[0].indices"));
+}
+
+#[test]
+fn test_scene_topology() {
+    use impasse::PrimitiveType;
+
+    // A quad with the indices 0, 1, 2, 3. It has normals, so the fan's flipped triangle doesn't split vertices.
+    let mut data = Vec::new();
+    for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    for index in [0u16, 1, 2, 3] {
+        data.extend_from_slice(&index.to_le_bytes());
+    }
+
+    let load = |name: &str, modes: &[i32]| {
+        let primitives = modes.iter()
+            .map(|mode| format!(r#"{{ "attributes": {{ "POSITION": 0, "NORMAL": 0 }}, "indices": 1, "material": 0, "mode": {mode} }}"#))
+            .collect::<Vec<_>>().join(", ");
+
+        load_triangle(name, &format!(r#",
+            "buffers": [ {} ],
+            "bufferViews": [
+                {{ "buffer": 0, "byteOffset": 0, "byteLength": 48 }},
+                {{ "buffer": 0, "byteOffset": 48, "byteLength": 8 }}
+            ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3" }},
+                {{ "bufferView": 1, "componentType": 5123, "count": 4, "type": "SCALAR" }}
+            ],
+            "meshes": [ {{ "primitives": [ {primitives} ] }} ]
+        "#, buffer(&data)))
+    };
+
+    // Triangle strip, triangle fan, line loop, line strip and points.
+    let scene = load("topology", &[5, 6, 2, 3, 0]).unwrap();
+    assert_eq!(scene.meshes.len(), 3);
+
+    let triangles = &scene.meshes[0];
+    assert_eq!(triangles.primitive_type, PrimitiveType::Triangles);
+    assert_eq!(triangles.indices, vec![0, 1, 2, 1, 3, 2, 5, 6, 4, 6, 7, 4]);
+
+    let lines = &scene.meshes[1];
+    assert_eq!(lines.primitive_type, PrimitiveType::Lines);
+    assert_eq!(lines.indices, vec![0, 1, 1, 2, 2, 3, 3, 0, 4, 5, 5, 6, 6, 7]);
+
+    let points = &scene.meshes[2];
+    assert_eq!(points.primitive_type, PrimitiveType::Points);
+    assert_eq!(points.indices, vec![0, 1, 2, 3]);
+
+    // Four indices aren't a whole number of triangles.
+    let error = load("topology_incomplete", &[4]).unwrap_err();
+    assert_eq!(error.path(), Some("meshes[0].primitives[0].indices"));
+}
+
+#[test]
+fn test_scene_vertex_colors_and_uv_sets() {
+    let mut data = triangle_data();
+    for value in [0.0f32, 0.0, 1.0, 0.0, 0.0, 1.0, 0.5, 0.5, 0.25, 0.25, 0.75, 0.75] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(&[255, 0, 0, 255, 0, 255, 0, 51, 0, 0, 255, 0]);
+    for value in [65535u16, 0, 0, 0, 65535, 0, 0, 0, 65535] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    let scene = load_triangle("vertex_colors", &format!(r#",
+        "buffers": [ {} ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+            {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }},
+            {{ "buffer": 0, "byteOffset": 44, "byteLength": 48 }},
+            {{ "buffer": 0, "byteOffset": 92, "byteLength": 30 }}
+        ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }},
+            {{ "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC2" }},
+            {{ "bufferView": 2, "byteOffset": 24, "componentType": 5126, "count": 3, "type": "VEC2" }},
+            {{ "bufferView": 3, "componentType": 5121, "normalized": true, "count": 3, "type": "VEC4" }},
+            {{ "bufferView": 3, "byteOffset": 12, "componentType": 5123, "normalized": true, "count": 3, "type": "VEC3" }}
+        ],
+        "meshes": [ {{ "primitives": [
+            {{ "attributes": {{ "POSITION": 0, "TEXCOORD_0": 2, "TEXCOORD_1": 3, "COLOR_0": 4 }}, "indices": 1, "material": 0 }},
+            {{ "attributes": {{ "POSITION": 0, "TEXCOORD_1": 2, "COLOR_0": 5 }}, "indices": 1, "material": 0 }},
+            {{ "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0 }}
+        ] }} ]
+    "#, buffer(&data))).unwrap();
+
+    let vertices = &scene.meshes[0].vertices;
+    assert_eq!(vertices.len(), 9);
+
+    // The sets don't overwrite each other.
+    assert_eq!((vertices[1].tex_coord.x, vertices[1].tex_coord_1.x), (1.0, 0.25));
+    assert_eq!((vertices[2].tex_coord.y, vertices[2].tex_coord_1.y), (1.0, 0.75));
+    assert_eq!((vertices[4].tex_coord.x, vertices[4].tex_coord_1.x), (0.0, 1.0));
+
+    // Normalized RGBA bytes.
+    let color = vertices[1].color;
+    assert_eq!((color.x, color.y, color.z, color.w), (0.0, 1.0, 0.0, 0.2));
+
+    // Normalized RGB shorts are opaque.
+    let color = vertices[5].color;
+    assert_eq!((color.x, color.y, color.z, color.w), (0.0, 0.0, 1.0, 1.0));
+
+    // Vertices without colors are white.
+    let color = vertices[8].color;
+    assert_eq!((color.x, color.y, color.z, color.w), (1.0, 1.0, 1.0, 1.0));
+}
+
+#[test]
+fn test_scene_tangents() {
+    // Two triangles sharing an edge, with the texture mirrored across it.
+    let mut data = Vec::new();
+    let positions = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0];
+    let normals = [0.0f32, 0.0, 1.0].repeat(4);
+    let tex_coords = [0.0f32, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0];
+    let tangents = [1.0f32, 0.0, 0.0, -1.0].repeat(4);
+    for value in positions.iter().chain(&normals).chain(&tex_coords).chain(&tangents) {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    for index in [0u16, 1, 2, 0, 2, 3] {
+        data.extend_from_slice(&index.to_le_bytes());
+    }
+
+    let scene = load_triangle("tangents", &format!(r#",
+        "buffers": [ {} ],
+        "bufferViews": [ {{ "buffer": 0, "byteOffset": 0, "byteLength": 192 }}, {{ "buffer": 0, "byteOffset": 192, "byteLength": 12 }} ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3" }},
+            {{ "bufferView": 0, "byteOffset": 48, "componentType": 5126, "count": 4, "type": "VEC3" }},
+            {{ "bufferView": 0, "byteOffset": 96, "componentType": 5126, "count": 4, "type": "VEC2" }},
+            {{ "bufferView": 0, "byteOffset": 128, "componentType": 5126, "count": 4, "type": "VEC4" }},
+            {{ "bufferView": 1, "componentType": 5123, "count": 6, "type": "SCALAR" }}
+        ],
+        "meshes": [
+            {{ "primitives": [ {{ "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }}, "indices": 4, "material": 0, "targets": [ {{ "POSITION": 0 }} ] }} ] }},
+            {{ "primitives": [ {{ "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2, "TANGENT": 3 }}, "indices": 4, "material": 0 }} ] }},
+            {{ "primitives": [ {{ "attributes": {{ "POSITION": 0, "TEXCOORD_0": 2, "TANGENT": 3 }}, "indices": 4, "material": 0 }} ] }}
+        ]
+    "#, buffer(&data))).unwrap();
+
+    let generated = &scene.meshes[0];
+    let vector = |v: impasse::Vec3| [v.x, v.y, v.z].map(|c| c.round() + 0.0);
+
+    // The shared vertices face opposite ways in the second triangle, so they're split.
+    assert_eq!(generated.vertices.len(), 6);
+    assert_eq!(generated.indices[..3], [0, 1, 2]);
+    assert_eq!(generated.indices[3..], [4, 5, 3]);
+    assert_eq!(generated.blend_shapes[0].positions.len(), 6);
+    assert_eq!(generated.blend_shapes[0].positions[5].y, 1.0);
+
+    assert_eq!(vector(generated.vertices[0].tangent), [1.0, 0.0, 0.0]);
+    assert_eq!(vector(generated.vertices[0].bitangent), [0.0, 1.0, 0.0]);
+    assert_eq!(vector(generated.vertices[4].tangent), [-1.0, 0.0, 0.0]);
+    assert_eq!(vector(generated.vertices[4].bitangent), [0.0, 1.0, 0.0]);
+    assert_eq!(generated.vertices[4].tex_coord.x, generated.vertices[0].tex_coord.x);
+
+    // Imported tangents are kept, and the bitangent follows their handedness.
+    let imported = &scene.meshes[1];
+    assert_eq!(imported.vertices.len(), 4);
+    assert_eq!(vector(imported.vertices[3].tangent), [1.0, 0.0, 0.0]);
+    assert_eq!(vector(imported.vertices[3].bitangent), [0.0, -1.0, 0.0]);
+
+    // Imported tangents belong to the file's normals, so they're generated again along with the normals.
+    let regenerated = &scene.meshes[2];
+    assert_eq!(regenerated.vertices.len(), 6);
+    assert_eq!(regenerated.indices, generated.indices);
+    assert_eq!(vector(regenerated.vertices[0].normal), [0.0, 0.0, 1.0]);
+    assert_eq!(vector(regenerated.vertices[0].bitangent), [0.0, 1.0, 0.0]);
+    assert_eq!(vector(regenerated.vertices[4].tangent), [-1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_scene_generated_normals() {
+    use impasse::{importers::{gltf::Gltf, FileResolver}, ImportOptions, NormalGeneration};
+
+    // Two triangles folded at a right angle along the edge from vertex 0 to vertex 1.
+    let mut data = Vec::new();
+    for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    for index in [0u16, 1, 2, 0, 3, 1] {
+        data.extend_from_slice(&index.to_le_bytes());
+    }
+
+    let json = format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "buffers": [ {} ],
+        "bufferViews": [ {{ "buffer": 0, "byteOffset": 0, "byteLength": 48 }}, {{ "buffer": 0, "byteOffset": 48, "byteLength": 12 }} ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5123, "count": 6, "type": "SCALAR" }}
+        ],
+        "meshes": [ {{ "primitives": [ {{ "attributes": {{ "POSITION": 0 }}, "indices": 1 }} ] }} ]
+    }}"#, buffer(&data));
+
+    let load = |normals: NormalGeneration| {
+        let gltf = Gltf::from_bytes(json.as_bytes(), &mut FileResolver).unwrap();
+        impasse::Scene::from_gltf_document(gltf, &ImportOptions { normals }).unwrap()
+    };
+    let normal = |mesh: &impasse::Mesh, vertex: usize| {
+        let n = mesh.vertices[vertex].normal;
+        [n.x, n.y, n.z].map(|c| (c * 1000.0).round() / 1000.0)
+    };
+
+    // Flat normals split the shared edge.
+    let mut flat = load(NormalGeneration::Flat);
+    let mesh = &mut flat.meshes[0];
+    assert_eq!(mesh.vertices.len(), 6);
+    assert_eq!(normal(mesh, 0), [0.0, 0.0, 1.0]);
+    assert_eq!(normal(mesh, mesh.indices[3] as usize), [0.0, 1.0, 0.0]);
+
+    // The fold is sharper than the crease angle, so it stays hard.
+    let sharp = load(NormalGeneration::Smooth { crease_angle: 60f32.to_radians() });
+    assert_eq!(sharp.meshes[0].vertices.len(), 6);
+
+    // The fold is smoothed, and the corners on the edge have the same angle in both triangles.
+    let smooth = load(NormalGeneration::Smooth { crease_angle: 100f32.to_radians() });
+    let smooth = &smooth.meshes[0];
+    assert_eq!(smooth.vertices.len(), 4);
+    assert_eq!(normal(smooth, 0), [0.0, 0.707, 0.707]);
+    assert_eq!(normal(smooth, 1), [0.0, 0.707, 0.707]);
+    assert_eq!(normal(smooth, 2), [0.0, 0.0, 1.0]);
+
+    // Loaded meshes can be smoothed too, across the vertices flat normals split.
+    mesh.generate_normals(NormalGeneration::Smooth { crease_angle: 100f32.to_radians() });
+    assert_eq!(mesh.vertices.len(), 6);
+    assert_eq!(normal(mesh, mesh.indices[3] as usize), [0.0, 0.707, 0.707]);
+    assert_eq!(normal(mesh, 2), [0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn test_scene_index_types() {
+    // The triangle's indices as unsigned bytes and unsigned ints, after the positions.
+    let mut data = triangle_data();
+    data.truncate(36);
+    data.extend_from_slice(&[0, 1, 2, 0]);
+    for index in [2u32, 1, 0] {
+        data.extend_from_slice(&index.to_le_bytes());
+    }
+
+    let load = |name: &str, meshes: &str| load_triangle(name, &format!(r#",
+        "buffers": [ {} ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+            {{ "buffer": 0, "byteOffset": 36, "byteLength": 3 }},
+            {{ "buffer": 0, "byteOffset": 40, "byteLength": 12 }}
+        ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5121, "count": 3, "type": "SCALAR" }},
+            {{ "bufferView": 2, "componentType": 5125, "count": 3, "type": "SCALAR" }},
+            {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "SCALAR" }}
+        ],
+        "meshes": [ {meshes} ]
+    "#, buffer(&data)));
+
+    let scene = load("index_types", r#"
+        { "primitives": [ { "attributes": { "POSITION": 0, "NORMAL": 0 }, "indices": 1, "material": 0 } ] },
+        { "primitives": [ { "attributes": { "POSITION": 0, "NORMAL": 0 }, "indices": 2, "material": 0 } ] },
+        { "primitives": [
+            { "attributes": { "POSITION": 0, "NORMAL": 0 }, "material": 0 },
+            { "attributes": { "POSITION": 0, "NORMAL": 0 }, "material": 0, "mode": 5 }
+        ] }
+    "#).unwrap();
+
+    assert_eq!(scene.meshes[0].indices, vec![0, 1, 2]);
+    assert_eq!(scene.meshes[1].indices, vec![2, 1, 0]);
+
+    // Primitives without indices use every vertex in order.
+    assert_eq!(scene.meshes[2].vertices.len(), 6);
+    assert_eq!(scene.meshes[2].indices, vec![0, 1, 2, 3, 4, 5]);
+
+    // Three vertices can't be split into lines.
+    let lines = load("index_types_lines", r#"{ "primitives": [ { "attributes": { "POSITION": 0 }, "material": 0, "mode": 1 } ] }"#);
+    assert_eq!(lines.unwrap_err().path(), Some("meshes[0].primitives[0]"));
+
+    // Indices must be unsigned integers.
+    let floats = load("index_types_floats", r#"{ "primitives": [ { "attributes": { "POSITION": 0 }, "indices": 3, "material": 0 } ] }"#);
+    assert_eq!(floats.unwrap_err().path(), Some("accessors[3].componentType"));
+}
+
+#[test]
+fn test_scene_nodes() {
+    use impasse::Mat4;
+
+    let scene = load_triangle("nodes", r#",
+        "scene": 1,
+        "scenes": [ { "nodes": [ 3 ] }, { "name": "Main", "nodes": [ 0 ] } ],
+        "nodes": [
+            { "name": "Root", "translation": [1.0, 2.0, 3.0], "children": [ 1 ] },
+            { "rotation": [0.0, 0.7071068, 0.0, 0.7071068], "scale": [2.0, 2.0, 2.0], "children": [ 2 ] },
+            { "mesh": 0, "matrix": [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 5, 1] },
+            { "mesh": 0 }
+        ]
+    "#).unwrap();
+
+    assert_eq!(scene.nodes.len(), 4);
+    assert_eq!(scene.nodes[0].name.as_deref(), Some("Root"));
+    assert_eq!(scene.nodes[0].children, vec![1]);
+    assert_eq!(scene.nodes[2].parent, Some(1));
+    assert_eq!(scene.nodes[2].meshes, vec![0]);
+    assert_eq!(scene.nodes[3].parent, None);
+
+    assert_eq!(scene.active_scene, Some(1));
+    assert_eq!(scene.scenes[1].name.as_deref(), Some("Main"));
+    assert_eq!(scene.root_nodes(), &[0]);
+
+    // Rotating (0, 0, 5) by 90 degrees about y and scaling it by 2 gives (10, 0, 0).
+    let world = scene.world_matrix(2);
+    let expected = [(world.m41, 11.0), (world.m42, 2.0), (world.m43, 3.0), (world.m11, 0.0), (world.m13, -2.0), (world.m31, 2.0)];
+    for (actual, expected) in expected {
+        assert!((actual - expected).abs() < 1e-5, "{actual} != {expected}");
+    }
+
+    let matrices = scene.world_matrices();
+    assert_eq!(matrices.len(), 4);
+    assert_eq!(matrices[3], Mat4::IDENTITY);
+    assert!((matrices[2].m41 - world.m41).abs() < 1e-5);
+
+    // Files without scenes show every node that has no parent.
+    let scene = load_triangle("nodes_no_scene", r#",
+        "nodes": [ { "children": [ 2 ] }, { }, { "mesh": 0 } ]
+    "#).unwrap();
+    assert_eq!(scene.root_nodes(), &[0, 1]);
+
+    let error = load_triangle("nodes_cycle", r#",
+        "nodes": [ { "children": [ 1 ] }, { "children": [ 0 ] } ]
+    "#).unwrap_err();
+    assert!(matches!(error, ImportError::InvalidData { .. }), "{error}");
+
+    let error = load_triangle("nodes_two_parents", r#",
+        "nodes": [ { "children": [ 2 ] }, { "children": [ 2 ] }, { } ]
+    "#).unwrap_err();
+    assert_eq!(error.path(), Some("nodes[1].children[0]"));
+}
+
+#[test]
+fn test_scene_texture_transform() {
+    let scene = load_triangle("texture_transform", r#",
+        "images": [ { "uri": "trim.png" } ],
+        "textures": [ { "source": 0 } ],
+        "materials": [ {
+            "pbrMetallicRoughness": {
+                "baseColorTexture": {
+                    "index": 0,
+                    "extensions": {
+                        "KHR_texture_transform": { "offset": [0.5, 0.25], "rotation": 1.5, "scale": [4.0, 2.0], "texCoord": 1 }
+                    }
+                }
+            },
+            "emissiveTexture": { "index": 0, "texCoord": 1 }
+        } ]
+    "#).unwrap();
+
+    let textures = &scene.materials[0].textures;
+
+    let albedo = &textures[0];
+    assert!(matches!(albedo.t_type, impasse::TextureType::Albedo));
+    assert_eq!(albedo.tex_coord, 1);
+    assert_eq!(albedo.transform.offset.x, 0.5);
+    assert_eq!(albedo.transform.offset.y, 0.25);
+    assert_eq!(albedo.transform.rotation, 1.5);
+    assert_eq!(albedo.transform.scale.x, 4.0);
+
+    let emissive = &textures[1];
+    assert_eq!(emissive.tex_coord, 1);
+    assert_eq!(emissive.transform.rotation, 0.0);
+    assert_eq!(emissive.transform.scale.y, 1.0);
+}
+
+#[test]
+fn test_scene_quantized_attributes() {
+    let mut data = Vec::new();
+    // Positions, as normalized shorts padded to 8 bytes.
+    for position in [[0i16, 0, 0], [32767, 0, -32767], [0, -32768, 16384]] {
+        for value in position {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[0, 0]);
+    }
+    // Normals, as normalized bytes padded to 4 bytes.
+    for normal in [[0i8, 127, 0], [-127, 0, 0], [0, 0, 127]] {
+        data.extend(normal.iter().map(|v| *v as u8));
+        data.push(0);
+    }
+    // Texture coordinates, as unnormalized unsigned bytes padded to 4 bytes.
+    for tex_coord in [[0u8, 0], [1, 0], [0, 2]] {
+        data.extend_from_slice(&tex_coord);
+        data.extend_from_slice(&[0, 0]);
+    }
+
+    let scene = load_json("quantized", &format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "extensionsUsed": [ "KHR_mesh_quantization" ],
+        "extensionsRequired": [ "KHR_mesh_quantization" ],
+        "buffers": [ {} ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 24, "byteStride": 8 }},
+            {{ "buffer": 0, "byteOffset": 24, "byteLength": 24, "byteStride": 4 }}
+        ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5122, "normalized": true, "count": 3, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5120, "normalized": true, "count": 3, "type": "VEC3" }},
+            {{ "bufferView": 1, "byteOffset": 12, "componentType": 5121, "count": 3, "type": "VEC2" }}
+        ],
+        "materials": [ {{ }} ],
+        "meshes": [ {{ "primitives": [ {{ "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }}, "material": 0 }} ] }} ]
+    }}"#, buffer(&data))).unwrap();
+
+    let vertices = &scene.meshes[0].vertices;
+    assert_eq!(vertices.len(), 3);
+
+    assert_eq!(vertices[1].position.x, 1.0);
+    assert_eq!(vertices[1].position.z, -1.0);
+    assert_eq!(vertices[2].position.y, -1.0);
+    assert!((vertices[2].position.z - 0.5).abs() < 0.001);
+
+    assert_eq!(vertices[0].normal.y, 1.0);
+    assert_eq!(vertices[1].normal.x, -1.0);
+    assert_eq!(vertices[2].normal.z, 1.0);
+
+    assert_eq!(vertices[1].tex_coord.x, 1.0);
+    assert_eq!(vertices[2].tex_coord.y, 2.0);
+}
+
+#[test]
+fn test_scene_accessor_out_of_range() {
+    let scene = load_triangle("accessor_out_of_range", r#",
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 10, "type": "VEC3" },
+            { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }
+        ]
+    "#);
+    assert!(scene.is_err());
+
+    // Attributes that are never read still set the number of vertices, which must fit in memory.
+    let huge = load_triangle("accessor_out_of_range_huge", r#",
+        "accessors": [ { "componentType": 5123, "count": 2147483647, "type": "VEC4" } ],
+        "meshes": [ { "primitives": [ { "attributes": { "JOINTS_0": 0 }, "material": 0, "mode": 0 } ] } ]
+    "#);
+    assert!(matches!(huge, Err(ImportError::InvalidData { ref path, .. }) if path == "meshes[0].primitives[0].attributes"));
+}
+
+/// Loads a glTF whose only primitive is the given Draco data, with float positions, optional texture coordinates
+/// and indices of the given component type.
+fn load_draco(name: &str, draco: &[u8], tex_coords: bool, index_type: u32) -> Result<impasse::Scene, ImportError> {
+    let (accessor, attribute, semantic) = if tex_coords {
+        (r#", { "componentType": 5126, "count": 3, "type": "VEC2" }"#, r#", "TEXCOORD_0": 2"#, r#", "TEXCOORD_0": 1"#)
+    } else {
+        ("", "", "")
+    };
+
+    load_json(name, &format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "extensionsUsed": [ "KHR_draco_mesh_compression" ],
+        "extensionsRequired": [ "KHR_draco_mesh_compression" ],
+        "buffers": [ {} ],
+        "bufferViews": [ {{ "buffer": 0, "byteOffset": 0, "byteLength": {} }} ],
+        "accessors": [
+            {{ "componentType": 5126, "count": 3, "type": "VEC3" }},
+            {{ "componentType": {index_type}, "count": 3, "type": "SCALAR" }}
+            {accessor}
+        ],
+        "materials": [ {{ }} ],
+        "meshes": [ {{ "primitives": [ {{
+            "attributes": {{ "POSITION": 0 {attribute} }},
+            "indices": 1,
+            "material": 0,
+            "extensions": {{ "KHR_draco_mesh_compression": {{ "bufferView": 0, "attributes": {{ "POSITION": 0 {semantic} }} }} }}
+        }} ] }} ]
+    }}"#, buffer(draco), draco.len()))
+}
+
+const DRACO_POSITIONS: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+
+#[test]
+fn test_scene_draco_sequential() {
+    // Header: version 2.2, triangle mesh, sequential encoding, no flags.
+    let mut data = b"DRACO\x02\x02\x01\x00\x00\x00".to_vec();
+    // One face and three points, with uncompressed indices.
+    data.extend_from_slice(&[1, 3, 1, 2, 0, 1]);
+    // One attribute decoder with generic float positions and quantized texture coordinates.
+    data.extend_from_slice(&[1, 2, 0, 9, 3, 0, 0, 3, 9, 2, 0, 1, 0, 2]);
+    for value in DRACO_POSITIONS {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    // No prediction, with one byte zigzag values of (0, 0), (3, 0) and (0, 3).
+    data.extend_from_slice(&[0xFE, 0, 1, 0, 0, 6, 0, 0, 6]);
+    // Quantized from a minimum of zero, with a range of one and two bits.
+    for value in [0.0f32, 0.0, 1.0] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.push(2);
+
+    let scene = load_draco("draco_sequential", &data, true, 5123).unwrap();
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.indices, [2, 0, 1]);
+    assert_eq!(mesh.vertices.len(), 3);
+
+    assert_eq!(mesh.vertices[1].position.x, 1.0);
+    assert_eq!(mesh.vertices[2].position.y, 1.0);
+    assert_eq!(mesh.vertices[1].tex_coord.x, 1.0);
+    assert_eq!(mesh.vertices[2].tex_coord.y, 1.0);
+    assert_eq!(mesh.vertices[0].tex_coord.x, 0.0);
+}
+
+#[test]
+fn test_scene_draco_edgebreaker() {
+    // Header: version 2.2, triangle mesh, edgebreaker encoding with the standard traversal.
+    let mut data = b"DRACO\x02\x02\x01\x01\x00\x00\x00".to_vec();
+    // Three vertices, one face, no attribute data, one symbol and no splits.
+    data.extend_from_slice(&[3, 1, 0, 1, 0, 0]);
+    // The E symbol, then a start face on the boundary.
+    data.extend_from_slice(&[1, 0x07, 0xFF, 1, 0x01]);
+    // One vertex attribute decoder for generic float positions.
+    data.extend_from_slice(&[1, 0xFF, 0, 0, 1, 0, 9, 3, 0, 0, 0]);
+    for value in DRACO_POSITIONS {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    let scene = load_draco("draco_edgebreaker", &data, false, 5123).unwrap();
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.vertices.len(), 3);
+
+    let mut positions = mesh.indices.iter().map(|&i| {
+        let position = mesh.vertices[i as usize].position;
+        [position.x, position.y, position.z]
+    }).collect::<Vec<_>>();
+    positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(positions, [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]]);
+}
+
+#[test]
+fn test_scene_draco_invalid() {
+    let truncated = load_draco("draco_truncated", b"DRACO\x02\x02\x01\x00\x00\x00\x01\x03\x01\x02", false, 5123);
+    assert!(matches!(truncated.unwrap_err(), ImportError::InvalidData { .. }));
+
+    let version = load_draco("draco_version", b"DRACO\x01\x03\x01\x00\x00\x00", false, 5123);
+    assert!(matches!(version.unwrap_err(), ImportError::Unsupported { .. }));
+
+    // No faces and 0xFFFFFFFF points, which can't fit in the rest of the data.
+    let points = load_draco("draco_points", b"DRACO\x02\x02\x01\x00\x00\x00\x00\xFF\xFF\xFF\xFF\x0F\x01\x00", false, 5123);
+    assert!(matches!(points.unwrap_err(), ImportError::InvalidData { .. }));
+
+    // Point 299 can't be an unsigned byte index.
+    let mut data = b"DRACO\x02\x02\x01\x00\x00\x00".to_vec();
+    data.extend_from_slice(&[1, 0xAC, 0x02, 1]);
+    for index in [299u16, 0, 1] {
+        data.extend_from_slice(&index.to_le_bytes());
+    }
+    data.extend_from_slice(&[1, 1, 0, 9, 3, 0, 0, 0]);
+    data.extend_from_slice(&[0; 300 * 12]);
+    assert!(load_draco("draco_index_type", &data, false, 5123).is_ok());
+    let index_type = load_draco("draco_index_type_byte", &data, false, 5121).unwrap_err();
+    assert!(matches!(index_type, ImportError::InvalidData { ref message, .. } if message.contains("299")), "{index_type}");
+}
+
+/// Encodes up to 16 vertices with the meshopt vertex codec, storing every byte group uncompressed.
+fn meshopt_vertices(data: &[u8], stride: usize) -> Vec<u8> {
+    let mut encoded = vec![0xA0];
+    for k in 0..stride {
+        encoded.push(0x03);
+
+        let mut group = [0u8; 16];
+        let mut previous = data[k];
+        for (i, vertex) in data.chunks_exact(stride).enumerate() {
+            let delta = vertex[k].wrapping_sub(previous) as i8;
+            group[i] = ((delta << 1) ^ (delta >> 7)) as u8;
+            previous = vertex[k];
+        }
+
+        encoded.extend_from_slice(&group);
+    }
+
+    // The tail is padded to 32 bytes, and ends with the first vertex.
+    encoded.extend(std::iter::repeat_n(0, 32usize.saturating_sub(stride)));
+    encoded.extend_from_slice(&data[..stride]);
+    encoded
+}
+
+#[test]
+fn test_scene_meshopt() {
+    // Positions as exponential floats: a 24 bit mantissa with an exponent of -1 in the high byte.
+    let mut positions = Vec::new();
+    for value in [0i32, 0, 0, 2, 0, 0, 0, 4, 0, 2, 4, -2] {
+        positions.extend_from_slice(&((value as u32 & 0xFFFFFF) | 0xFF000000).to_le_bytes());
+    }
+
+    let mut data = meshopt_vertices(&positions, 12);
+    let positions_length = data.len();
+    // Two triangles, (0, 1, 2) from the code table and (2, 1, 3) from the edge FIFO.
+    data.extend_from_slice(&[0xE1, 0xF0, 0x10, 0x00, 0x76, 0x87, 0x56, 0x67, 0x78, 0xA9, 0x86, 0x65, 0x89, 0x68, 0x98, 0x01, 0x69, 0x00, 0x00]);
+
+    let scene = load_json("meshopt", &format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "extensionsUsed": [ "EXT_meshopt_compression" ],
+        "extensionsRequired": [ "EXT_meshopt_compression" ],
+        "buffers": [
+            {},
+            {{ "byteLength": 60, "extensions": {{ "EXT_meshopt_compression": {{ "fallback": true }} }} }}
+        ],
+        "bufferViews": [
+            {{ "buffer": 1, "byteOffset": 0, "byteLength": 48, "byteStride": 12,
+               "extensions": {{ "EXT_meshopt_compression": {{ "buffer": 0, "byteLength": {positions_length}, "byteStride": 12, "count": 4, "mode": "ATTRIBUTES", "filter": "EXPONENTIAL" }} }} }},
+            {{ "buffer": 1, "byteOffset": 48, "byteLength": 12,
+               "extensions": {{ "EXT_meshopt_compression": {{ "buffer": 0, "byteOffset": {positions_length}, "byteLength": 19, "byteStride": 2, "count": 6, "mode": "TRIANGLES" }} }} }}
+        ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5123, "count": 6, "type": "SCALAR" }}
+        ],
+        "materials": [ {{ }} ],
+        "meshes": [ {{ "primitives": [ {{ "attributes": {{ "POSITION": 0, "NORMAL": 0 }}, "indices": 1, "material": 0 }} ] }} ]
+    }}"#, buffer(&data))).unwrap();
+
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.indices, [0, 1, 2, 2, 1, 3]);
+    assert_eq!(mesh.vertices[1].position.x, 1.0);
+    assert_eq!(mesh.vertices[2].position.y, 2.0);
+    assert_eq!(mesh.vertices[3].position.z, -1.0);
+}
+
+/// The JSON for a single triangle, whose buffer is given by `buffer`.
+fn triangle_json(buffer: &str) -> String {
+    format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "buffers": [ {buffer} ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+            {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+        ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+        ],
+        "materials": [ {{ }} ],
+        "meshes": [ {{ "primitives": [ {{ "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0 }} ] }} ]
+    }}"#)
+}
+
+#[test]
+fn test_scene_from_bytes() {
+    let data = triangle_data();
+
+    // External buffers are loaded through the callback.
+    let json = triangle_json(r#"{ "uri": "triangle.bin", "byteLength": 42 }"#);
+    let mut requested = Vec::new();
+    let scene = impasse::Scene::from_bytes(json.as_bytes(), &mut |uri: &str| {
+        requested.push(uri.to_string());
+        Ok(data.clone())
+    }).unwrap();
+    assert_eq!(requested, ["triangle.bin"]);
+    assert_eq!(scene.meshes[0].indices, [0, 1, 2]);
+
+    let missing = impasse::Scene::from_bytes(json.as_bytes(), &mut |_: &str| Err(std::io::ErrorKind::NotFound.into()));
+    let Err(error) = missing else { panic!("Missing buffers should fail to load.") };
+    assert!(matches!(&error, ImportError::Resource { error, .. } if error.kind() == std::io::ErrorKind::NotFound));
+    assert_eq!(error.path(), Some("buffers[0].uri"));
+
+    // A GLB with the buffer in its BIN chunk, read from the middle of a larger stream.
+    let mut json = triangle_json(r#"{ "byteLength": 42 }"#).into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let mut bin = data.clone();
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let mut pak = b"PAK!".to_vec();
+    pak.extend_from_slice(b"glTF");
+    pak.extend_from_slice(&2u32.to_le_bytes());
+    pak.extend_from_slice(&(12 + 8 + json.len() as u32 + 8 + bin.len() as u32).to_le_bytes());
+    pak.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    pak.extend_from_slice(b"JSON");
+    pak.extend_from_slice(&json);
+    pak.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    pak.extend_from_slice(b"BIN\0");
+    pak.extend_from_slice(&bin);
+
+    let mut reader = std::io::Cursor::new(pak);
+    reader.set_position(4);
+    let scene = impasse::Scene::from_reader(reader, &mut |_: &str| panic!("GLB buffers should not be resolved.")).unwrap();
+    assert_eq!(scene.meshes[0].vertices[1].position.x, 1.0);
+}
+
+#[test]
+fn test_scene_resource_resolver() {
+    use std::path::{Path, PathBuf};
+
+    /// Serves the document and its buffer from memory, recording every lookup.
+    struct MemoryResolver {
+        requests: Vec<(PathBuf, String)>
+    }
+
+    impl impasse::importers::ResourceResolver for MemoryResolver {
+        fn resolve(&mut self, base: &Path, uri: &str) -> Result<Vec<u8>, std::io::Error> {
+            self.requests.push((base.to_path_buf(), uri.to_string()));
+
+            match uri {
+                "models/triangle.gltf" => Ok(triangle_json(r#"{ "uri": "My%20Triangle%2Ebin", "byteLength": 42 }"#).into_bytes()),
+                "My Triangle.bin" => Ok(triangle_data()),
+                _ => Err(std::io::ErrorKind::NotFound.into())
+            }
+        }
+    }
+
+    let mut resolver = MemoryResolver { requests: Vec::new() };
+    let scene = impasse::Scene::from_gltf_with("models/triangle.gltf", &mut resolver).unwrap();
+    assert_eq!(scene.meshes[0].indices, [0, 1, 2]);
+    assert_eq!(resolver.requests, [
+        (PathBuf::new(), "models/triangle.gltf".to_string()),
+        (PathBuf::from("models"), "My Triangle.bin".to_string())
+    ]);
+
+    // The default resolver reads from the filesystem, relative to the document.
+    let directory = std::env::temp_dir().join("impasse_resolver");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("My Triangle.bin"), triangle_data()).unwrap();
+    std::fs::write(directory.join("triangle.gltf"), triangle_json(r#"{ "uri": "My%20Triangle.bin", "byteLength": 42 }"#)).unwrap();
+
+    let scene = impasse::Scene::from_gltf(directory.join("triangle.gltf").to_str().unwrap()).unwrap();
+    assert_eq!(scene.meshes[0].vertices[2].position.y, 1.0);
+
+    assert_eq!(impasse::importers::percent_decode("a%20b%zz%4"), b"a b%zz%4");
+}
+
+#[cfg(feature = "images")]
+#[test]
+fn test_scene_decode_textures() {
+    fn png(width: u32, color: png::ColorType, depth: png::BitDepth, palette: Option<(&[u8], &[u8])>, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, width, 1);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        if let Some((palette, trns)) = palette {
+            encoder.set_palette(palette);
+            encoder.set_trns(trns);
+        }
+        encoder.write_header().unwrap().write_image_data(data).unwrap();
+        out
+    }
+
+    fn jpeg(progressive: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut encoder = jpeg_encoder::Encoder::new(&mut out, 100);
+        encoder.set_progressive(progressive);
+        encoder.encode(&[255, 255, 255].repeat(64), 8, 8, jpeg_encoder::ColorType::Rgb).unwrap();
+        out
+    }
+
+    let images = [
+        // A 2 pixel palette image, with a transparent first entry.
+        png(2, png::ColorType::Indexed, png::BitDepth::Eight, Some((&[255, 0, 0, 0, 0, 255], &[0])), &[0, 1]),
+        png(1, png::ColorType::Grayscale, png::BitDepth::Sixteen, None, &[0x12, 0x34]),
+        jpeg(false),
+        jpeg(true)
+    ];
+
+    let uris = images.iter().map(|image| format!(r#"{{ "uri": "data:;base64,{}" }}"#, base64::engine::general_purpose::STANDARD.encode(image))).collect::<Vec<_>>();
+    let mut scene = load_triangle("decode_textures", &format!(r#",
+        "images": [ {} ],
+        "textures": [ {{ "source": 0 }}, {{ "source": 1 }}, {{ "source": 2 }}, {{ "source": 3 }} ],
+        "materials": [ {{ "pbrMetallicRoughness": {{ "baseColorTexture": {{ "index": 0 }} }}, "normalTexture": {{ "index": 1 }} }} ]
+    "#, uris.join(", "))).unwrap();
+
+    scene.decode_textures(std::path::Path::new(""), &mut |_: &str| panic!("Every image is embedded.")).unwrap();
+
+    let palette = scene.textures[0].pixels.as_ref().unwrap();
+    assert_eq!((palette.width, palette.height, palette.channels, palette.bit_depth), (2, 1, 4, 8));
+    assert_eq!(palette.color_space, impasse::ColorSpace::Srgb);
+    assert_eq!(palette.data, [255, 0, 0, 0, 0, 0, 255, 255]);
+
+    let gray = scene.textures[1].pixels.as_ref().unwrap();
+    assert_eq!(gray.bit_depth, 16);
+    assert_eq!(gray.color_space, impasse::ColorSpace::Linear);
+    assert_eq!(gray.data, [0x34, 0x12, 0x34, 0x12, 0x34, 0x12, 0xFF, 0xFF]);
+
+    for texture in &scene.textures[2..] {
+        let pixels = texture.pixels.as_ref().unwrap();
+        assert_eq!((pixels.width, pixels.height, pixels.data.len()), (8, 8, 8 * 8 * 4));
+        assert!(pixels.data.iter().all(|&value| value >= 250));
+    }
+
+    // Other formats are left alone, but corrupt images are errors.
+    scene.textures[0].data = Some(b"RIFF....WEBP".to_vec());
+    scene.textures[1].data = Some(b"\x89PNG\r\n\x1a\ncorrupt".to_vec());
+    scene.textures[0].pixels = None;
+    let corrupt = scene.decode_textures(std::path::Path::new(""), &mut |_: &str| panic!("Every image is embedded."));
+    assert!(matches!(corrupt, Err(ImportError::InvalidData { ref path, .. }) if path == "textures[1]"));
+    assert!(scene.textures[0].pixels.is_none());
+
+    // External images that can't be loaded point at their texture.
+    scene.textures[1].data = None;
+    scene.textures[1].path = Some("missing.png".to_string());
+    let missing = scene.decode_textures(std::path::Path::new(""), &mut |_: &str| Err(std::io::ErrorKind::NotFound.into()));
+    assert!(matches!(missing, Err(ImportError::Resource { ref path, .. }) if path == "textures[1]"));
+}